@@ -0,0 +1,346 @@
+//! Long-term goals defined in `assets/achievements.ron`, or an asset pack's. Unlocks are saved
+//! in the user data directory and announced with a toast.
+
+use std::{collections::BTreeSet, time::Duration};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    definitions::{self, Definitions},
+    enemy::EnemyKillEvent,
+    hud::RunClock,
+    leaf::Leaf,
+    manifest, persist,
+    player::PlayerPos,
+    state_transition::StateTransitionEvent,
+    stats::RunStats,
+    title::AttractMode,
+    GameAssets, GameMode, GameState, NoPersistence, Score,
+};
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Achievements>()
+            .init_resource::<RunProgress>()
+            .init_resource::<AchievementsWindow>()
+            .add_event::<AchievementUnlocked>()
+            .add_startup_system(spawn_toast_area)
+            .add_system(read_definitions.in_schedule(OnExit(GameState::AssetLoading)))
+            .add_system(
+                count_runs
+                    .run_if(not(resource_exists::<AttractMode>()))
+                    .run_if(not(resource_exists::<NoPersistence>()))
+                    .run_if(mode_counts),
+            )
+            .add_system(
+                check_achievements
+                    .in_set(OnUpdate(GameState::InGame))
+                    .run_if(not(resource_exists::<AttractMode>()))
+                    .run_if(not(resource_exists::<NoPersistence>()))
+                    .run_if(mode_counts),
+            )
+            .add_system(
+                record_best_score
+                    .in_schedule(OnEnter(GameState::GameOver))
+                    .run_if(not(resource_exists::<AttractMode>()))
+                    .run_if(not(resource_exists::<NoPersistence>())),
+            )
+            .add_system(spawn_toasts)
+            .add_system(expire_toasts)
+            .add_system(achievements_window.in_set(OnUpdate(GameState::Title)));
+    }
+}
+
+const DEFINITIONS: &str = "achievements";
+const PROGRESS: &str = "achievements.ron";
+const TOAST_TIME: Duration = Duration::from_secs(4);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub condition: Condition,
+}
+
+/// See `assets/achievements.ron`.
+#[derive(Debug, Clone, Deserialize)]
+pub enum Condition {
+    ShotKills(u32),
+    BugsEaten(u32),
+    Combo(u32),
+    CellsVisited(u32),
+    Survive(f32),
+    StayBelowDecay { decay: f32, seconds: f32 },
+    Runs(u32),
+}
+
+/// What is kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Progress {
+    unlocked: BTreeSet<String>,
+    runs: u32,
+    /// Over all runs.
+    best_score: u32,
+}
+
+#[derive(Resource)]
+pub struct Achievements {
+    source: Handle<Definitions>,
+    /// Empty until the assets are loaded.
+    definitions: Vec<Achievement>,
+    progress: Progress,
+}
+
+impl FromWorld for Achievements {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            source: manifest::load(world, DEFINITIONS),
+            definitions: Vec::new(),
+            progress: persist::load_ron(PROGRESS),
+        }
+    }
+}
+
+impl Achievements {
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.progress.unlocked.contains(id)
+    }
+
+    pub fn name(&self, id: &str) -> Option<&str> {
+        self.definitions
+            .iter()
+            .find(|achievement| achievement.id == id)
+            .map(|achievement| achievement.name.as_str())
+    }
+
+    pub fn best_score(&self) -> u32 {
+        self.progress.best_score
+    }
+
+    /// Unlocks the locked achievements whose condition is `met`, saving if there were any.
+    fn unlock_where(
+        &mut self,
+        met: impl Fn(&Condition) -> bool,
+        unlocked: &mut EventWriter<AchievementUnlocked>,
+    ) {
+        let mut changed = false;
+        for achievement in &self.definitions {
+            if !self.progress.unlocked.contains(&achievement.id) && met(&achievement.condition) {
+                info!("unlocked achievement `{}`", achievement.id);
+                self.progress.unlocked.insert(achievement.id.clone());
+                unlocked.send(AchievementUnlocked(achievement.clone()));
+                changed = true;
+            }
+        }
+
+        if changed {
+            persist::save_ron(PROGRESS, &self.progress);
+        }
+    }
+}
+
+pub struct AchievementUnlocked(pub Achievement);
+
+/// Whether the list of achievements is shown on the title screen.
+#[derive(Resource, Default)]
+pub struct AchievementsWindow(pub bool);
+
+/// What the run stats don't cover.
+#[derive(Resource, Default)]
+struct RunProgress {
+    /// Highest decay of a leaf the frog stood on.
+    max_decay: f32,
+}
+
+#[derive(Component)]
+struct ToastArea;
+
+#[derive(Component)]
+struct Toast(Timer);
+
+/// Not in the tutorial, where the frog can stand still for as long as it likes.
+fn mode_counts(mode: Res<GameMode>) -> bool {
+    mode.has_scores() || mode.drowning_ends_run()
+}
+
+fn read_definitions(world: &mut World) {
+    let definitions = definitions::read(world, &world.resource::<Achievements>().source);
+    world.resource_mut::<Achievements>().definitions = definitions;
+}
+
+fn count_runs(
+    mut transitions: EventReader<StateTransitionEvent<GameState>>,
+    mut achievements: ResMut<Achievements>,
+    mut progress: ResMut<RunProgress>,
+    mut unlocked: EventWriter<AchievementUnlocked>,
+) {
+    if !transitions.iter().any(|ev| ev.to == GameState::InGame) {
+        return;
+    }
+
+    *progress = default();
+    achievements.progress.runs += 1;
+    persist::save_ron(PROGRESS, &achievements.progress);
+
+    let runs = achievements.progress.runs;
+    achievements.unlock_where(
+        |condition| matches!(*condition, Condition::Runs(n) if runs >= n),
+        &mut unlocked,
+    );
+}
+
+fn check_achievements(
+    mut kills: EventReader<EnemyKillEvent>,
+    stats: Res<RunStats>,
+    clock: Res<RunClock>,
+    player_pos: Res<PlayerPos>,
+    leaves: Query<&Leaf>,
+    mut progress: ResMut<RunProgress>,
+    mut achievements: ResMut<Achievements>,
+    mut unlocked: EventWriter<AchievementUnlocked>,
+) {
+    let shot_kills = kills.iter().map(|kill| kill.shot_kills).max().unwrap_or(0);
+    if let Some(leaf) = leaves.iter().find(|leaf| leaf.pos == player_pos.0) {
+        progress.max_decay = progress.max_decay.max(leaf.decay);
+    }
+
+    let bugs_eaten = stats.bugs_eaten.values().sum::<u32>();
+    let survived = clock.0.elapsed_secs();
+
+    achievements.unlock_where(
+        |condition| match *condition {
+            Condition::ShotKills(n) => shot_kills >= n,
+            Condition::BugsEaten(n) => bugs_eaten >= n,
+            Condition::Combo(n) => stats.longest_combo >= n,
+            Condition::CellsVisited(n) => stats.cells_visited >= n,
+            Condition::Survive(seconds) => survived >= seconds,
+            Condition::StayBelowDecay { decay, seconds } => {
+                survived >= seconds && progress.max_decay <= decay
+            }
+            Condition::Runs(_) => false,
+        },
+        &mut unlocked,
+    );
+}
+
+fn record_best_score(
+    score: Res<Score>,
+    mode: Res<GameMode>,
+    mut achievements: ResMut<Achievements>,
+) {
+    if mode.has_scores() && score.0 > achievements.progress.best_score {
+        achievements.progress.best_score = score.0;
+        persist::save_ron(PROGRESS, &achievements.progress);
+    }
+}
+
+fn spawn_toast_area(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                gap: Size::all(Val::Px(8.0)),
+                ..default()
+            },
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+        ToastArea,
+    ));
+}
+
+fn spawn_toasts(
+    mut commands: Commands,
+    mut unlocked: EventReader<AchievementUnlocked>,
+    area: Query<Entity, With<ToastArea>>,
+    assets: Res<GameAssets>,
+) {
+    let Ok(area) = area.get_single() else { return };
+
+    for AchievementUnlocked(achievement) in unlocked.iter() {
+        let style = |font_size| TextStyle {
+            font: assets.font.clone(),
+            font_size,
+            color: Color::WHITE,
+        };
+
+        commands.entity(area).with_children(|parent| {
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        },
+                        background_color: Color::rgba(0., 0.2, 0.3, 0.8).into(),
+                        ..default()
+                    },
+                    Toast(Timer::new(TOAST_TIME, TimerMode::Once)),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_sections([
+                        TextSection::new(
+                            format!("Achievement unlocked: {}\n", achievement.name),
+                            style(30.0),
+                        ),
+                        TextSection::new(achievement.description.clone(), style(22.0)),
+                    ]));
+                });
+        });
+    }
+}
+
+fn expire_toasts(mut commands: Commands, mut toasts: Query<(Entity, &mut Toast)>, time: Res<Time>) {
+    for (entity, mut toast) in &mut toasts {
+        if toast.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn achievements_window(
+    mut egui_contexts: EguiContexts,
+    mut window: ResMut<AchievementsWindow>,
+    achievements: Res<Achievements>,
+) {
+    if !window.0 {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("Achievements")
+        .open(&mut open)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Grid::new("achievements")
+                .striped(true)
+                .show(ui, |ui| {
+                    for achievement in &achievements.definitions {
+                        let unlocked = achievements.is_unlocked(&achievement.id);
+                        ui.label(if unlocked { "✔" } else { "" });
+                        ui.add_enabled(unlocked, egui::Label::new(&achievement.name));
+                        ui.label(&achievement.description);
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if !open {
+        window.0 = false;
+    }
+}
@@ -0,0 +1,144 @@
+//! Rule-based bot that plays the game: used for the attract-mode demo and for headless
+//! smoke tests.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    enemy::{Bug, BUG_SPEED},
+    env::{Action, BugObservation, LeafObservation, LeafrogEnv, Move, Observation},
+    leaf::{Leaf, LEAF_SIZE, SAFE_DECAY},
+    player::{Player, PlayerAction, PlayerControlSet, PlayerPos, ShootTongueEvent},
+    GameState, Rotation,
+};
+
+/// Jump away once the current leaf has decayed this much.
+const JUMP_DECAY: f32 = 0.3;
+/// Don't shoot at bugs farther than this.
+const TONGUE_REACH: f32 = 500.;
+
+const MOVES: [(Move, IVec2); 4] = [
+    (Move::Up, IVec2::Y),
+    (Move::Down, IVec2::NEG_Y),
+    (Move::Left, IVec2::NEG_X),
+    (Move::Right, IVec2::X),
+];
+
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            drive
+                .before(PlayerControlSet)
+                .in_set(OnUpdate(GameState::InGame))
+                .run_if(resource_exists::<Autopilot>()),
+        );
+    }
+}
+
+/// While this resource exists, the autopilot controls the frog.
+#[derive(Resource)]
+pub struct Autopilot;
+
+pub fn decide(obs: &Observation) -> Action {
+    let decay_at = |cell: IVec2| {
+        obs.leaves
+            .iter()
+            .find(|leaf| leaf.cell == cell)
+            .map(|leaf| leaf.decay)
+    };
+
+    let movement = if decay_at(obs.frog).map_or(true, |decay| decay >= JUMP_DECAY) {
+        MOVES
+            .iter()
+            .filter_map(|&(movement, dir)| Some((movement, decay_at(obs.frog + dir)?)))
+            .filter(|&(_, decay)| decay < SAFE_DECAY)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(Move::None, |(movement, _)| movement)
+    } else {
+        Move::None
+    };
+
+    let frog = obs.frog.as_vec2() * LEAF_SIZE;
+    let tongue = obs
+        .bugs
+        .iter()
+        .filter(|bug| bug.pos.distance(frog) < TONGUE_REACH)
+        .min_by(|a, b| {
+            a.pos
+                .distance_squared(frog)
+                .total_cmp(&b.pos.distance_squared(frog))
+        })
+        .map(|bug| {
+            // Lead the target by roughly the time the tongue takes to reach it.
+            let lead = (bug.pos.distance(frog) / 4000.).max(0.15);
+            let heading = Vec2::new(-bug.heading.sin(), bug.heading.cos());
+            bug.pos + heading * BUG_SPEED * lead
+        });
+
+    Action { movement, tongue }
+}
+
+fn drive(
+    leaves: Query<&Leaf>,
+    bugs: Query<(&Transform, &Rotation), With<Bug>>,
+    player_pos: Res<PlayerPos>,
+    mut player: Query<&mut ActionState<PlayerAction>, With<Player>>,
+    mut shoot: EventWriter<ShootTongueEvent>,
+) {
+    let obs = Observation {
+        leaves: leaves
+            .iter()
+            .map(|leaf| LeafObservation {
+                cell: leaf.pos,
+                decay: leaf.decay,
+            })
+            .collect(),
+        bugs: bugs
+            .iter()
+            .map(|(tr, rot)| BugObservation {
+                pos: tr.translation.truncate(),
+                heading: rot.0,
+            })
+            .collect(),
+        frog: player_pos.0,
+    };
+
+    let action = decide(&obs);
+
+    let mut action_state = player.single_mut();
+    match action.movement {
+        Move::None => {}
+        Move::Up => action_state.press(PlayerAction::Up),
+        Move::Down => action_state.press(PlayerAction::Down),
+        Move::Left => action_state.press(PlayerAction::Left),
+        Move::Right => action_state.press(PlayerAction::Right),
+    }
+
+    if let Some(target) = action.tongue {
+        shoot.send(ShootTongueEvent(target));
+    }
+}
+
+/// Lets the autopilot play a headless run until the frog drowns or `limit` has passed.
+/// Returns how long the frog survived.
+pub fn smoke_test(seed: u64, limit: Duration) -> Duration {
+    let mut env = LeafrogEnv::new();
+    let step_time = env.step_time();
+
+    let mut obs = env.reset(seed);
+    let mut survived = Duration::ZERO;
+    while survived < limit {
+        let step = env.step(decide(&obs));
+        if step.done {
+            break;
+        }
+        obs = step.observation;
+        survived += step_time;
+    }
+
+    survived
+}
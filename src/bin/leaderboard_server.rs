@@ -0,0 +1,147 @@
+//! Reference leaderboard server, keeping scores in a RON file.
+//!
+//! `leaderboard_server [ADDR] [FILE]`, by default on `127.0.0.1:7878` with `leaderboard.ron`.
+//! See `leafrog::leaderboard` for the protocol.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+
+use leafrog::{
+    highscore::HighScore,
+    leaderboard::{FileLeaderboard, Leaderboard},
+    GameMode,
+};
+
+/// Largest request body accepted.
+const MAX_BODY: usize = 64 * 1024;
+/// Most scores a single `/top` request can ask for.
+const MAX_TOP: usize = 100;
+/// How long a client may keep the server waiting, as it handles one request at a time.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let address = args.next().unwrap_or_else(|| "127.0.0.1:7878".to_string());
+    let board = FileLeaderboard::new(args.next().unwrap_or_else(|| "leaderboard.ron".to_string()));
+
+    let listener = TcpListener::bind(&address).unwrap_or_else(|e| {
+        eprintln!("can't listen on {address}: {e}");
+        std::process::exit(1);
+    });
+    println!("listening on {address}");
+
+    // One request at a time, so that submissions don't race on the file.
+    for stream in listener.incoming() {
+        let result = stream
+            .map_err(|e| e.to_string())
+            .and_then(|stream| handle(stream, &board));
+        if let Err(e) = result {
+            eprintln!("{e}");
+        }
+    }
+}
+
+fn handle(mut stream: TcpStream, board: &FileLeaderboard) -> Result<(), String> {
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .and_then(|()| stream.set_write_timeout(Some(TIMEOUT)))
+        .map_err(|e| e.to_string())?;
+
+    let (status, body) = match read_request(&stream) {
+        Ok((method, target, body)) => respond(board, &method, &target, &body),
+        Err(e) => ("400 Bad Request", e),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: application/ron\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n\
+         {body}",
+        body.len(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Method, target and body of the request.
+fn read_request(stream: &TcpStream) -> Result<(String, String, String), String> {
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    let mut request_line = line.split_whitespace();
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Err(format!("malformed request line {line:?}"));
+    };
+    let (method, target) = (method.to_string(), target.to_string());
+
+    let mut length = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).map_err(|e| e.to_string())?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().map_err(|_| "bad Content-Length")?;
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(format!("body over {MAX_BODY} bytes"));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    let body = String::from_utf8(body).map_err(|e| e.to_string())?;
+
+    Ok((method, target, body))
+}
+
+fn respond(
+    board: &FileLeaderboard,
+    method: &str,
+    target: &str,
+    body: &str,
+) -> (&'static str, String) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let result = match (method, path) {
+        ("GET", "/top") => top(board, query),
+        ("POST", "/submit") => ron::from_str::<HighScore>(body)
+            .map_err(|e| e.to_string())
+            .and_then(|entry| board.submit(&entry))
+            .map(|()| String::new()),
+        _ => return ("404 Not Found", format!("no {method} {path}")),
+    };
+
+    match result {
+        Ok(body) => ("200 OK", body),
+        Err(e) => ("400 Bad Request", e),
+    }
+}
+
+fn top(board: &FileLeaderboard, query: &str) -> Result<String, String> {
+    let mut mode = GameMode::default();
+    let mut level = None;
+    let mut n = 10;
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        match key {
+            "mode" => mode = ron::from_str(value).map_err(|e| format!("bad mode: {e}"))?,
+            "level" => level = Some(value.parse().map_err(|_| "bad level")?),
+            "n" => n = value.parse::<usize>().map_err(|_| "bad n")?.min(MAX_TOP),
+            _ => {}
+        }
+    }
+
+    let entries = board.top(mode, level, n)?;
+    ron::to_string(&entries).map_err(|e| e.to_string())
+}
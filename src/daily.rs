@@ -0,0 +1,204 @@
+//! [`GameMode::Daily`]: one pond and bug pattern per day, the same for everyone.
+//!
+//! The day is the local one, so it changes at midnight wherever the player is. Players in
+//! different time zones get the same pond, only not at the same time.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use chrono::{Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    persist, settings::Settings, title::AttractMode, GameMode, GameRng, GameState, NoPersistence,
+    Score,
+};
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(persist::load_ron::<DailyRecords>(FILE))
+            .init_resource::<DailyRun>()
+            .add_system(
+                start_daily
+                    .in_base_set(CoreSet::StateTransitions)
+                    .before(apply_state_transition::<GameState>),
+            )
+            .add_system(
+                record_daily_best
+                    .in_schedule(OnEnter(GameState::GameOver))
+                    .run_if(not(resource_exists::<AttractMode>()))
+                    .run_if(not(resource_exists::<NoPersistence>())),
+            );
+    }
+}
+
+const FILE: &str = "daily.ron";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Day 0 of [`day_number`].
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
+}
+
+/// Today's local date, as `YYYY-MM-DD`.
+pub fn today() -> String {
+    Local::now().date_naive().format(DATE_FORMAT).to_string()
+}
+
+/// Days since 1970-01-01, by the local calendar.
+pub fn day_number() -> u32 {
+    (Local::now().date_naive() - epoch()).num_days() as u32
+}
+
+/// The date of [`day_number`] `days`, as `YYYY-MM-DD`.
+pub fn date(days: u32) -> String {
+    let date = epoch() + Days::new(days.into());
+    date.format(DATE_FORMAT).to_string()
+}
+
+/// The [`GameRng`] seed of day `day`.
+pub fn seed(day: u32) -> u64 {
+    // splitmix64, so that consecutive days get unrelated seeds.
+    let mut z = u64::from(day).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DayRecord {
+    pub best: u32,
+    pub attempts: u32,
+}
+
+/// Daily challenge results by date, saved in the user data directory.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct DailyRecords(pub BTreeMap<String, DayRecord>);
+
+impl DailyRecords {
+    /// One line about the challenge of `date`.
+    pub fn summary(&self, date: &str) -> String {
+        match self.0.get(date) {
+            Some(record) if record.attempts > 0 => format!(
+                "Daily {date}: best {}, {} attempt{}",
+                record.best,
+                record.attempts,
+                if record.attempts == 1 { "" } else { "s" },
+            ),
+            _ => format!("Daily {date}: not played yet"),
+        }
+    }
+}
+
+/// The daily challenge being played, if it is one.
+#[derive(Resource, Default)]
+pub struct DailyRun(pub Option<DailyAttempt>);
+
+pub struct DailyAttempt {
+    pub date: String,
+    /// Whether the score may count, see [`Settings::daily_first_attempt_only`].
+    pub counts: bool,
+}
+
+impl DailyRun {
+    /// Whether the run may enter the high-score table.
+    pub fn counts(&self) -> bool {
+        self.0.as_ref().map_or(true, |attempt| attempt.counts)
+    }
+}
+
+/// Seeds the generator right before a daily run starts, so that the pond and bugs only depend
+/// on the day.
+pub fn start_daily(
+    next_state: Res<NextState<GameState>>,
+    mode: Res<GameMode>,
+    attract: Option<Res<AttractMode>>,
+    no_persistence: Option<Res<NoPersistence>>,
+    settings: Res<Settings>,
+    mut records: ResMut<DailyRecords>,
+    mut run: ResMut<DailyRun>,
+    mut rng: ResMut<GameRng>,
+) {
+    if next_state.0 != Some(GameState::InGame) {
+        return;
+    }
+
+    run.0 = None;
+    if *mode != GameMode::Daily {
+        return;
+    }
+
+    let day = day_number();
+    *rng = GameRng::with_seed(seed(day));
+    if attract.is_some() || no_persistence.is_some() {
+        return;
+    }
+
+    let date = date(day);
+    let record = records.0.entry(date.clone()).or_default();
+    record.attempts += 1;
+    let counts = record.attempts == 1 || !settings.daily_first_attempt_only;
+    persist::save_ron(FILE, &*records);
+
+    run.0 = Some(DailyAttempt { date, counts });
+}
+
+fn record_daily_best(run: Res<DailyRun>, score: Res<Score>, mut records: ResMut<DailyRecords>) {
+    let Some(attempt) = &run.0 else { return };
+    if !attempt.counts {
+        return;
+    }
+
+    let record = records.0.entry(attempt.date.clone()).or_default();
+    if score.0 > record.best {
+        record.best = score.0;
+        persist::save_ron(FILE, &*records);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_are_stable() {
+        // Everyone has to get the same pond, whatever the build or platform.
+        assert_eq!(seed(0), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(seed(1), 0x910a_2dec_8902_5cc1);
+        assert_eq!(seed(20000), 0xe7aa_7324_ae5d_121e);
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(11016), "2000-02-29");
+        // Unlike 2000, 2100 is no leap year.
+        assert_eq!(date(47540), "2100-02-28");
+        assert_eq!(date(47541), "2100-03-01");
+    }
+
+    #[test]
+    fn consecutive_days_differ() {
+        for day in 19000..20000 {
+            assert_ne!(seed(day), seed(day + 1));
+        }
+    }
+
+    #[test]
+    fn counts() {
+        let attempt = |counts| {
+            DailyRun(Some(DailyAttempt {
+                date: "2024-01-01".to_string(),
+                counts,
+            }))
+        };
+
+        // Not a daily run at all.
+        assert!(DailyRun(None).counts());
+        assert!(attempt(true).counts());
+        assert!(!attempt(false).counts());
+    }
+}
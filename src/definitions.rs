@@ -0,0 +1,71 @@
+//! Lists the game is made of, like the achievements, skins and puzzle levels. They are RON
+//! files listed in the asset manifest, so asset packs can replace them, and are read once
+//! [`GameState::AssetLoading`] is over.
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+use crate::manifest::Placeholder;
+
+pub struct DefinitionsPlugin;
+
+impl Plugin for DefinitionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Definitions>()
+            .add_asset_loader(DefinitionsLoader);
+    }
+}
+
+/// The text of a definitions file, parsed by [`read`] into whatever it lists.
+#[derive(TypeUuid)]
+#[uuid = "4b9f1c2e-8d3a-4e6b-9a51-2f7c0d8e6a13"]
+pub struct Definitions(String);
+
+/// Without its file, a list is empty.
+impl Placeholder for Definitions {
+    fn placeholder() -> Option<Self> {
+        None
+    }
+}
+
+struct DefinitionsLoader;
+
+impl AssetLoader for DefinitionsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let text = std::str::from_utf8(bytes)?.to_string();
+            load_context.set_default_asset(LoadedAsset::new(Definitions(text)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// The entries of the loaded definitions `handle`, with none if they're missing or broken.
+pub fn read<T: DeserializeOwned>(world: &World, handle: &Handle<Definitions>) -> Vec<T> {
+    let Some(Definitions(text)) = world.resource::<Assets<Definitions>>().get(handle) else {
+        // The manifest has complained already.
+        return Vec::new();
+    };
+
+    ron::from_str(text).unwrap_or_else(|e| {
+        let server = world.resource::<AssetServer>();
+        let path = server
+            .get_handle_path(handle)
+            .map_or_else(|| format!("{handle:?}"), |p| p.path().display().to_string());
+        warn!("ignoring {path}: {e}");
+        Vec::new()
+    })
+}
@@ -1,39 +1,41 @@
-use crate::{InGameTag, Rotation};
+use crate::{manifest, run_scope, GameMode, GameRng, Rotation};
 use bevy::prelude::*;
-use iyes_progress::prelude::AssetsLoading;
 use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::f32;
 
 use super::GameState;
 
 pub struct EnemyPlugin;
 
+pub const BUG_SPEED: f32 = 300.0;
+
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<EnemyAssets>()
             .add_event::<EnemyKillEvent>()
-            .add_system(spawn_bugs.in_schedule(OnEnter(GameState::InGame)))
+            // After the leaves, as both draw from `GameRng` and a seed has to reproduce the run.
+            .add_system(
+                spawn_bugs
+                    .in_schedule(OnEnter(GameState::InGame))
+                    .after(crate::ingame_startup),
+            )
             .add_systems(
-                (enemy_move_system, enemy_reset).in_set(OnUpdate(GameState::InGame))
+                (enemy_move_system, follow_paths, enemy_reset).in_set(OnUpdate(GameState::InGame)),
             );
     }
 }
 
 #[derive(Resource)]
-struct EnemyAssets {
+pub struct EnemyAssets {
     texture: Handle<Image>,
 }
 
 impl FromWorld for EnemyAssets {
     fn from_world(world: &mut World) -> Self {
-        let assets = world.get_resource::<AssetServer>().unwrap();
-
-        let texture = assets.load("bug.png");
-
-        let mut loading = world.get_resource_mut::<AssetsLoading>().unwrap();
-        loading.add(texture.clone());
-
-        EnemyAssets { texture }
+        EnemyAssets {
+            texture: manifest::load(world, "bug"),
+        }
     }
 }
 
@@ -48,28 +50,100 @@ fn enemy_move_system(mut q: Query<(&mut Transform, &mut Rotation, &Velocity)>, t
     });
 }
 
-fn random_initial_pos_rot() -> (Vec2, Rotation) {
-    let random_angle = || fastrand::f32() * (2. * f32::consts::PI);
+fn follow_paths(mut q: Query<(&mut Transform, &mut Rotation, &mut BugPath)>, time: Res<Time>) {
+    for (mut tr, mut rot, mut path) in &mut q {
+        if path.points.len() < 2 {
+            continue;
+        }
+
+        let to_next = path.points[path.next] - tr.translation.truncate();
+        let step = path.speed * time.delta_seconds();
+        if to_next.length() <= step {
+            let z = tr.translation.z;
+            tr.translation = path.points[path.next].extend(z);
+            path.next = (path.next + 1) % path.points.len();
+        } else {
+            tr.translation += (to_next.normalize() * step).extend(0.);
+        }
+        // Repeated waypoints have no direction to face.
+        if to_next.length_squared() > f32::EPSILON {
+            rot.0 = Vec2::Y.angle_between(to_next);
+        }
+    }
+}
+
+fn random_initial_pos_rot(rng: &mut GameRng) -> (Vec2, Rotation) {
+    let mut random_angle = || rng.f32() * (2. * f32::consts::PI);
     let pos = Quat::from_rotation_z(random_angle())
         .mul_vec3(Vec3::Y * 600.)
         .truncate();
     (pos, Rotation(random_angle()))
 }
 
-fn spawn_bugs(mut commands: Commands, assets: Res<EnemyAssets>) {
-    for _ in 0..20 {
-        let (pos, rot) = random_initial_pos_rot();
-        commands
-            .spawn(
-                (
-                    BugBundle::new(assets.texture.clone(), pos, rot),
-                    Sensor,
-                    Collider::cuboid(20., 20.),
-                    CollisionGroups::new(Group::GROUP_1, Group::ALL),
-                    ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC,
-            )
-            )
-            .insert(InGameTag);
+fn spawn_bugs(
+    mut commands: Commands,
+    assets: Res<EnemyAssets>,
+    mut rng: ResMut<GameRng>,
+    mode: Res<GameMode>,
+) {
+    let count = match *mode {
+        GameMode::Endless | GameMode::Daily => 20,
+        // Bugs are all that counts there.
+        GameMode::TimeAttack => 30,
+        // Levels and the tutorial place their own, see `spawn_path_bug`.
+        GameMode::Puzzle | GameMode::Tutorial => 0,
+    };
+    for _ in 0..count {
+        let (pos, rot) = random_initial_pos_rot(&mut rng);
+        spawn_bug(&mut commands, &assets, pos, rot);
+    }
+}
+
+fn spawn_bug<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    assets: &EnemyAssets,
+    pos: Vec2,
+    rot: Rotation,
+) -> bevy::ecs::system::EntityCommands<'w, 's, 'a> {
+    let mut e = commands.spawn((
+        BugBundle::new(assets.texture.clone(), pos, rot),
+        Sensor,
+        Collider::cuboid(20., 20.),
+        CollisionGroups::new(Group::GROUP_1, Group::ALL),
+        ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC,
+    ));
+    e.insert(run_scope());
+    e
+}
+
+/// Spawns a bug that follows `path` instead of roaming, and is gone for good once eaten.
+pub fn spawn_path_bug<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    assets: &EnemyAssets,
+    path: BugPath,
+) -> bevy::ecs::system::EntityCommands<'w, 's, 'a> {
+    let pos = path.points.first().copied().unwrap_or_default();
+    let mut e = spawn_bug(commands, assets, pos, default());
+    e.remove::<Velocity>().insert(path);
+    e
+}
+
+/// Waypoints a bug goes through in a loop, in world coordinates. A single one keeps it still.
+#[derive(Component)]
+pub struct BugPath {
+    pub points: Vec<Vec2>,
+    /// In pixels per second.
+    pub speed: f32,
+    next: usize,
+}
+
+impl BugPath {
+    pub fn new(points: Vec<Vec2>, speed: f32) -> Self {
+        Self {
+            points,
+            speed,
+            next: 1,
+        }
     }
 }
 
@@ -77,7 +151,25 @@ fn spawn_bugs(mut commands: Commands, assets: Res<EnemyAssets>) {
 struct Velocity(f32);
 
 #[derive(Component, Default)]
-struct Bug;
+pub struct Bug {
+    pub species: Species,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum Species {
+    #[default]
+    Fly,
+}
+
+impl Species {
+    pub fn name(self) -> &'static str {
+        match self {
+            Species::Fly => "Fly",
+        }
+    }
+}
 
 #[derive(Bundle)]
 struct BugBundle {
@@ -93,7 +185,7 @@ impl BugBundle {
         BugBundle {
             bug: default(),
             rotation,
-            velocity: Velocity(300.0),
+            velocity: Velocity(BUG_SPEED),
             sprite: SpriteBundle {
                 texture: res.clone(),
                 transform: Transform {
@@ -107,15 +199,29 @@ impl BugBundle {
     }
 }
 
-pub struct EnemyKillEvent(pub Entity);
+/// A bug caught by the tongue.
+pub struct EnemyKillEvent {
+    pub bug: Entity,
+    /// Where it was caught.
+    pub position: Vec2,
+    /// Bugs caught by this tongue shot so far, this one included.
+    pub shot_kills: u32,
+}
 
 fn enemy_reset(
+    mut commands: Commands,
     mut ev_kill: EventReader<EnemyKillEvent>,
-    mut q: Query<(&mut Transform, &mut Rotation), With<Bug>>,
+    mut q: Query<(&mut Transform, &mut Rotation, Option<&BugPath>), With<Bug>>,
+    mut rng: ResMut<GameRng>,
 ) {
     for ev in ev_kill.iter() {
-        let (mut transform, mut rot) = q.get_mut(ev.0).unwrap();
-        let (new_pos, new_rot) = random_initial_pos_rot();
+        // The bug is gone if the run was restarted since the kill.
+        let Ok((mut transform, mut rot, path)) = q.get_mut(ev.bug) else { continue };
+        if path.is_some() {
+            commands.entity(ev.bug).despawn();
+            continue;
+        }
+        let (new_pos, new_rot) = random_initial_pos_rot(&mut rng);
         *rot = new_rot;
         transform.translation = new_pos.extend(3.0);
     }
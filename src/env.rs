@@ -0,0 +1,306 @@
+//! Step-based environment for training agents and balance testing.
+//!
+//! The game runs headless on top of [`LeafrogPlugin`], with a simulated clock, so it runs
+//! as fast as the CPU allows.
+//!
+//! ```no_run
+//! use leafrog::env::{Action, LeafrogEnv, Move};
+//!
+//! let mut env = LeafrogEnv::new();
+//! let mut obs = env.reset(42);
+//! loop {
+//!     let action = Action {
+//!         movement: Move::Up,
+//!         tongue: obs.bugs.first().map(|bug| bug.pos),
+//!     };
+//!     let step = env.step(action);
+//!     obs = step.observation;
+//!     if step.done {
+//!         break;
+//!     }
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+    render::{settings::WgpuSettings, RenderPlugin},
+    time::TimeUpdateStrategy,
+    window::ExitCondition,
+};
+
+use crate::{
+    enemy::Bug,
+    leaf::Leaf,
+    loading::LoadingAssets,
+    player::{PlayerPos, ShootTongueEvent},
+    screen_transition::ScreenTransitionConfig,
+    GameRng, GameState, LeafrogPlugin, NoPersistence, Rotation, Score,
+};
+
+#[derive(Debug, Clone)]
+pub struct EnvConfig {
+    /// Simulated duration of a single frame.
+    pub frame_time: Duration,
+    /// Number of frames simulated by a single [`LeafrogEnv::step`].
+    pub frames_per_step: u32,
+    /// Subtracted from the reward of the step in which the frog dies.
+    pub death_penalty: f32,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            frame_time: Duration::from_secs_f32(1. / 60.),
+            frames_per_step: 6,
+            death_penalty: 10.,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Move {
+    #[default]
+    None,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Action {
+    pub movement: Move,
+    /// World position to shoot the tongue at.
+    pub tongue: Option<Vec2>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LeafObservation {
+    pub cell: IVec2,
+    /// `0.0` is a healthy leaf, `1.0` a sunk one.
+    pub decay: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct BugObservation {
+    pub pos: Vec2,
+    /// Heading in radians, counterclockwise from +Y.
+    pub heading: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Observation {
+    pub leaves: Vec<LeafObservation>,
+    pub bugs: Vec<BugObservation>,
+    pub frog: IVec2,
+}
+
+#[derive(Debug, Clone)]
+pub struct StepResult {
+    pub observation: Observation,
+    pub reward: f32,
+    pub done: bool,
+}
+
+pub struct LeafrogEnv {
+    app: App,
+    config: EnvConfig,
+    clock: Instant,
+    score: u32,
+    done: bool,
+}
+
+impl LeafrogEnv {
+    pub fn new() -> Self {
+        Self::with_config(EnvConfig::default())
+    }
+
+    pub fn with_config(config: EnvConfig) -> Self {
+        let mut app = App::new();
+        app.add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    // The window is never opened; it only exists for the systems that query it.
+                    primary_window: Some(Window::default()),
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                })
+                .set(RenderPlugin {
+                    wgpu_settings: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    },
+                })
+                .disable::<bevy::winit::WinitPlugin>()
+                .disable::<bevy::gilrs::GilrsPlugin>()
+                .disable::<bevy::log::LogPlugin>(),
+        )
+        .add_plugin(LeafrogPlugin)
+        .insert_resource(ScreenTransitionConfig {
+            duration: Duration::ZERO,
+        })
+        .insert_resource(NoPersistence);
+
+        let mut env = Self {
+            app,
+            config,
+            clock: Instant::now(),
+            score: 0,
+            done: true,
+        };
+
+        while env.state() == GameState::AssetLoading {
+            let failed = &env.app.world.resource::<LoadingAssets>().failed;
+            if !failed.is_empty() {
+                panic!("failed to load assets: {}", failed.join(", "));
+            }
+            env.update();
+            std::thread::yield_now();
+        }
+
+        env
+    }
+
+    /// Starts a new run whose pond and bugs are determined by `seed`.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        if self.state() == GameState::InGame {
+            self.goto(GameState::GameOver);
+        }
+        if self.state() == GameState::GameOver {
+            self.goto(GameState::Title);
+        }
+
+        self.app.insert_resource(GameRng::with_seed(seed));
+        self.goto(GameState::InGame);
+        // Let physics pick up the spawned colliders before the agent acts.
+        self.update();
+
+        self.score = 0;
+        self.done = false;
+        self.observe()
+    }
+
+    pub fn step(&mut self, action: Action) -> StepResult {
+        if self.done {
+            return StepResult {
+                observation: self.observe(),
+                reward: 0.,
+                done: true,
+            };
+        }
+
+        let key = match action.movement {
+            Move::None => None,
+            Move::Up => Some(KeyCode::Up),
+            Move::Down => Some(KeyCode::Down),
+            Move::Left => Some(KeyCode::Left),
+            Move::Right => Some(KeyCode::Right),
+        };
+
+        if let Some(key) = key {
+            self.send_key(key, ButtonState::Pressed);
+        }
+        if let Some(target) = action.tongue {
+            self.app.world.send_event(ShootTongueEvent(target));
+        }
+
+        for frame in 0..self.config.frames_per_step {
+            self.update();
+
+            // Only hold the key for one frame, so that one step makes at most one jump.
+            if frame == 0 {
+                if let Some(key) = key {
+                    self.send_key(key, ButtonState::Released);
+                }
+            }
+
+            if self.state() != GameState::InGame {
+                self.done = true;
+                break;
+            }
+        }
+
+        let mut reward = 0.;
+        let score = self.app.world.resource::<Score>().0;
+        reward += score as f32 - self.score as f32;
+        self.score = score;
+        if self.done {
+            reward -= self.config.death_penalty;
+        }
+
+        StepResult {
+            observation: self.observe(),
+            reward,
+            done: self.done,
+        }
+    }
+
+    /// Simulated time that passes in a single [`step`](Self::step).
+    pub fn step_time(&self) -> Duration {
+        self.config.frame_time * self.config.frames_per_step
+    }
+
+    pub fn observe(&mut self) -> Observation {
+        let world = &mut self.app.world;
+
+        let leaves = world
+            .query::<&Leaf>()
+            .iter(world)
+            .map(|leaf| LeafObservation {
+                cell: leaf.pos,
+                decay: leaf.decay,
+            })
+            .collect();
+
+        let bugs = world
+            .query_filtered::<(&Transform, &Rotation), With<Bug>>()
+            .iter(world)
+            .map(|(tr, rot)| BugObservation {
+                pos: tr.translation.truncate(),
+                heading: rot.0.rem_euclid(2. * std::f32::consts::PI),
+            })
+            .collect();
+
+        Observation {
+            leaves,
+            bugs,
+            frog: world.resource::<PlayerPos>().0,
+        }
+    }
+
+    fn state(&self) -> GameState {
+        self.app.world.resource::<State<GameState>>().0.clone()
+    }
+
+    fn goto(&mut self, state: GameState) {
+        self.app
+            .world
+            .resource_mut::<NextState<GameState>>()
+            .set(state);
+        self.update();
+    }
+
+    fn update(&mut self) {
+        self.clock += self.config.frame_time;
+        self.app
+            .insert_resource(TimeUpdateStrategy::ManualInstant(self.clock));
+        self.app.update();
+    }
+
+    fn send_key(&mut self, key_code: KeyCode, state: ButtonState) {
+        self.app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+        });
+    }
+}
+
+impl Default for LeafrogEnv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,9 +1,20 @@
 use std::time::Duration;
 
-use bevy::{prelude::*, audio::AudioSink};
+use bevy::prelude::*;
 use bevy_egui::EguiContexts;
 
-use crate::{BGMTrack, GameAssets, GameState, InGameTag};
+use crate::{
+    daily::{DailyRecords, DailyRun},
+    highscore::NameEntry,
+    hud::{format_time, RunClock},
+    player::DeathCause,
+    puzzle::{self, PuzzleRun, Puzzles},
+    state_scoped::DespawnOnExit,
+    stats::RunStats,
+    time_attack::DROWN_PENALTY,
+    title::AttractMode,
+    GameAssets, GameMode, GameState,
+};
 
 pub struct GameOverPlugin;
 
@@ -11,14 +22,14 @@ impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<GameOverState>()
             .add_system(setup_gameover.in_schedule(OnEnter(GameState::GameOver)))
-            .add_systems((despawn::<InGameTag>, despawn::<GameOverTag>).in_schedule(OnExit(GameState::GameOver)))
-            .add_system(control.in_set(OnUpdate(GameState::GameOver)));
+            .add_system(
+                control
+                    .in_set(OnUpdate(GameState::GameOver))
+                    .run_if(not(resource_exists::<NameEntry>())),
+            );
     }
 }
 
-#[derive(Component)]
-struct GameOverTag;
-
 #[derive(Default, Resource)]
 struct GameOverState {
     cooldown: Timer,
@@ -26,13 +37,25 @@ struct GameOverState {
 
 fn setup_gameover(
     mut commands: Commands,
-    audio_sinks: Res<Assets<AudioSink>>,
-    mut bgm: ResMut<BGMTrack>,
     mut state: ResMut<GameOverState>,
     game_assets: Res<GameAssets>,
+    attract: Option<Res<AttractMode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    stats: Res<RunStats>,
+    clock: Res<RunClock>,
+    mode: Res<GameMode>,
+    daily: Res<DailyRun>,
+    daily_records: Res<DailyRecords>,
+    puzzle_run: Res<PuzzleRun>,
+    puzzles: Res<Puzzles>,
 ) {
     info!("setup_gameover");
 
+    if attract.is_some() {
+        next_state.set(GameState::Title);
+        return;
+    }
+
     state.cooldown = Timer::new(Duration::from_millis(800), default());
 
     commands
@@ -43,20 +66,106 @@ fn setup_gameover(
                 align_items: AlignItems::Center,
                 ..default()
             },
-            text: Text::from_section(
-                "GAMEOVER",
-                TextStyle {
-                    font: game_assets.font.clone(),
-                    font_size: 150.0,
-                    color: Color::SEA_GREEN,
-                },
-            )
+            text: Text::from_sections([
+                TextSection::new(
+                    match *mode {
+                        GameMode::Endless | GameMode::Daily | GameMode::Tutorial => "GAMEOVER\n",
+                        GameMode::TimeAttack => "TIME UP\n",
+                        GameMode::Puzzle if puzzle_run.is_solved() => "SOLVED\n",
+                        GameMode::Puzzle => "FAILED\n",
+                    },
+                    TextStyle {
+                        font: game_assets.font.clone(),
+                        font_size: 150.0,
+                        color: Color::SEA_GREEN,
+                    },
+                ),
+                TextSection::new(
+                    stats_summary(&stats, &clock)
+                        + &daily_summary(&daily, &daily_records)
+                        + &puzzle_summary(*mode, &puzzle_run, &puzzles),
+                    TextStyle {
+                        font: game_assets.font.clone(),
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                TextSection::new(
+                    "Space / R: retry    Esc: title",
+                    TextStyle {
+                        font: game_assets.font.clone(),
+                        font_size: 40.0,
+                        color: Color::SEA_GREEN,
+                    },
+                ),
+            ])
             .with_alignment(TextAlignment::Center),
             ..default()
         })
-        .insert(GameOverTag);
+        .insert(DespawnOnExit::new(GameState::GameOver));
+}
 
-    bgm.stop(&audio_sinks);
+/// A few lines about the run, ending with an empty one.
+fn stats_summary(stats: &RunStats, clock: &RunClock) -> String {
+    let mut summary = match stats.death {
+        Some(DeathCause::Water) => "Jumped into the water\n",
+        Some(DeathCause::SunkLeaf) => "Sank with a leaf\n",
+        None => "",
+    }
+    .to_string();
+    if stats.drownings > 0 {
+        summary += &format!(
+            "Drowned {} times, -{}s\n",
+            stats.drownings,
+            (DROWN_PENALTY * stats.drownings).as_secs(),
+        );
+    }
+
+    summary += &format!(
+        "Survived {}    Jumps: {}    Leaves visited: {}\n",
+        format_time(clock.0.elapsed()),
+        stats.jumps,
+        stats.cells_visited,
+    );
+
+    let eaten = stats
+        .bugs_eaten
+        .iter()
+        .map(|(species, count)| format!("{} x{count}", species.name()))
+        .collect::<Vec<_>>();
+    let eaten = if eaten.is_empty() {
+        "none".to_string()
+    } else {
+        eaten.join(", ")
+    };
+    summary += &format!("Bugs eaten: {eaten}\n");
+
+    summary += &format!(
+        "Tongue: {}/{} hits ({:.0}%)    Longest combo: x{}\n\n",
+        stats.hits,
+        stats.shots,
+        stats.accuracy() * 100.,
+        stats.longest_combo.max(1),
+    );
+
+    summary
+}
+
+fn daily_summary(daily: &DailyRun, records: &DailyRecords) -> String {
+    let Some(attempt) = &daily.0 else { return String::new() };
+
+    let mut summary = records.summary(&attempt.date);
+    if !attempt.counts {
+        summary += " (this attempt doesn't count)";
+    }
+    summary + "\n\n"
+}
+
+fn puzzle_summary(mode: GameMode, run: &PuzzleRun, puzzles: &Puzzles) -> String {
+    match mode {
+        GameMode::Puzzle => puzzle::summary(run, puzzles),
+        _ => String::new(),
+    }
 }
 
 fn control(
@@ -72,15 +181,19 @@ fn control(
         return;
     }
 
-    if egui_contexts.ctx_mut().is_pointer_over_area() {
+    if keys.just_released(KeyCode::Escape) {
+        next_state.set(GameState::Title);
         return;
     }
 
-    if buttons.just_released(MouseButton::Left) || keys.just_released(KeyCode::Space) {
-        next_state.set(GameState::Title);
+    if egui_contexts.ctx_mut().is_pointer_over_area() {
+        return;
     }
-}
 
-fn despawn<C: Component>(mut commands: Commands, q: Query<Entity, With<C>>) {
-    q.for_each(|e| commands.entity(e).despawn());
+    if buttons.just_released(MouseButton::Left)
+        || keys.just_released(KeyCode::Space)
+        || keys.just_released(KeyCode::R)
+    {
+        next_state.set(GameState::InGame);
+    }
 }
@@ -0,0 +1,330 @@
+//! Local high-score table, saved in the user data directory.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    daily::{today, DailyRun},
+    hud::{format_time, RunClock},
+    persist,
+    puzzle::PuzzleRun,
+    stats::RunStats,
+    title::AttractMode,
+    GameMode, GameState, NoPersistence, RunSeed, Score,
+};
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(persist::load_ron::<HighScores>(FILE))
+            .init_resource::<HighScoreWindow>()
+            .add_system(
+                check_high_score
+                    .in_schedule(OnEnter(GameState::GameOver))
+                    .run_if(not(resource_exists::<AttractMode>()))
+                    .run_if(not(resource_exists::<NoPersistence>())),
+            )
+            .add_system(
+                name_entry
+                    .in_set(OnUpdate(GameState::GameOver))
+                    .run_if(resource_exists::<NameEntry>()),
+            )
+            .add_system(skip_name_entry.in_schedule(OnExit(GameState::GameOver)))
+            .add_system(high_score_window.in_set(OnUpdate(GameState::Title)));
+    }
+}
+
+const FILE: &str = "highscores.ron";
+/// Scores kept per mode and level.
+const TABLE_SIZE: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    /// `YYYY-MM-DD`, by the local calendar.
+    pub date: String,
+    pub score: u32,
+    /// In seconds.
+    pub time_survived: f32,
+    /// [`RunSeed`] of the run.
+    pub seed: u64,
+    pub mode: GameMode,
+    /// For modes that have levels.
+    pub level: Option<u32>,
+    #[serde(default)]
+    pub stats: RunStats,
+}
+
+impl HighScore {
+    /// The run that just ended, under `name`.
+    pub(crate) fn of_run(
+        name: String,
+        score: &Score,
+        clock: &RunClock,
+        seed: &RunSeed,
+        mode: GameMode,
+        level: Option<u32>,
+        stats: &RunStats,
+    ) -> Self {
+        Self {
+            name,
+            date: today(),
+            score: score.0,
+            time_survived: clock.0.elapsed_secs(),
+            seed: seed.0,
+            mode,
+            level,
+            stats: stats.clone(),
+        }
+    }
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighScores {
+    /// Offered again the next time a name is asked for.
+    pub last_name: String,
+    /// Sorted by score, best first.
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    pub fn top(&self, mode: GameMode, level: Option<u32>) -> impl Iterator<Item = &HighScore> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.mode == mode && entry.level == level)
+    }
+
+    pub fn qualifies(&self, mode: GameMode, level: Option<u32>, score: u32) -> bool {
+        let table = self.top(mode, level).collect::<Vec<_>>();
+        score > 0 && (table.len() < TABLE_SIZE || table.iter().any(|entry| score > entry.score))
+    }
+
+    pub fn insert(&mut self, entry: HighScore) {
+        let (mode, level) = (entry.mode, entry.level);
+        // After the entries with the same score, so that older ones rank higher.
+        let i = self
+            .entries
+            .partition_point(|other| other.score >= entry.score);
+        self.entries.insert(i, entry);
+
+        let mut kept = 0;
+        self.entries.retain(|entry| {
+            if entry.mode != mode || entry.level != level {
+                return true;
+            }
+            kept += 1;
+            kept <= TABLE_SIZE
+        });
+    }
+}
+
+/// A qualifying score waiting for the player's name.
+#[derive(Resource)]
+pub struct NameEntry {
+    entry: HighScore,
+    focused: bool,
+}
+
+/// Whether the high-score table is shown on the title screen.
+#[derive(Resource, Default)]
+pub struct HighScoreWindow(pub bool);
+
+fn check_high_score(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    score: Res<Score>,
+    clock: Res<RunClock>,
+    seed: Res<RunSeed>,
+    mode: Res<GameMode>,
+    puzzle: Res<PuzzleRun>,
+    stats: Res<RunStats>,
+    daily: Res<DailyRun>,
+) {
+    let level = puzzle.score_level(*mode);
+    if !mode.has_scores() || !daily.counts() || !high_scores.qualifies(*mode, level, score.0) {
+        return;
+    }
+
+    commands.insert_resource(NameEntry {
+        entry: HighScore::of_run(
+            high_scores.last_name.clone(),
+            &score,
+            &clock,
+            &seed,
+            *mode,
+            level,
+            &stats,
+        ),
+        focused: false,
+    });
+}
+
+fn name_entry(
+    mut commands: Commands,
+    mut egui_contexts: EguiContexts,
+    mut entry: ResMut<NameEntry>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    let mut done = false;
+
+    egui::Window::new("New high score!")
+        .anchor(egui::Align2::CENTER_BOTTOM, [0., -40.])
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label(format!("Score: {}", entry.entry.score));
+
+            let response = ui.text_edit_singleline(&mut entry.entry.name);
+            if !entry.focused {
+                response.request_focus();
+                entry.focused = true;
+            }
+            let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() || entered {
+                    let name = entry.entry.name.trim();
+                    entry.entry.name = if name.is_empty() { "Frog" } else { name }.to_string();
+
+                    high_scores.last_name = entry.entry.name.clone();
+                    high_scores.insert(entry.entry.clone());
+                    persist::save_ron(FILE, &*high_scores);
+                    done = true;
+                }
+                if ui.button("Skip").clicked() {
+                    done = true;
+                }
+            });
+        });
+
+    if done {
+        commands.remove_resource::<NameEntry>();
+    }
+}
+
+fn skip_name_entry(mut commands: Commands) {
+    commands.remove_resource::<NameEntry>();
+}
+
+fn high_score_window(
+    mut egui_contexts: EguiContexts,
+    mut window: ResMut<HighScoreWindow>,
+    high_scores: Res<HighScores>,
+    mode: Res<GameMode>,
+    puzzle: Res<PuzzleRun>,
+) {
+    if !window.0 {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new("High scores")
+        .open(&mut open)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            let level = puzzle.score_level(*mode);
+            score_table(ui, "high_scores", high_scores.top(*mode, level));
+        });
+
+    if !open {
+        window.0 = false;
+    }
+}
+
+/// Shows ranked `entries`, best first.
+pub fn score_table<'a>(
+    ui: &mut egui::Ui,
+    id: &str,
+    entries: impl IntoIterator<Item = &'a HighScore>,
+) {
+    let mut entries = entries.into_iter().peekable();
+    if entries.peek().is_none() {
+        ui.label("No scores yet.");
+        return;
+    }
+
+    egui::Grid::new(id).striped(true).show(ui, |ui| {
+        for heading in ["", "Name", "Score", "Time", "Date"] {
+            ui.strong(heading);
+        }
+        ui.end_row();
+
+        for (rank, entry) in entries.enumerate() {
+            ui.label(format!("{}.", rank + 1));
+            ui.label(&entry.name);
+            ui.label(entry.score.to_string());
+            ui.label(format_time(Duration::from_secs_f32(entry.time_survived)));
+            ui.label(&entry.date);
+            ui.end_row();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, mode: GameMode, score: u32) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            date: String::new(),
+            score,
+            time_survived: 0.,
+            seed: 0,
+            mode,
+            level: None,
+            stats: default(),
+        }
+    }
+
+    fn names(scores: &HighScores, mode: GameMode) -> Vec<&str> {
+        scores
+            .top(mode, None)
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn qualifies_until_the_table_is_full() {
+        let mut scores = HighScores::default();
+        assert!(!scores.qualifies(GameMode::Endless, None, 0));
+        for _ in 0..TABLE_SIZE {
+            assert!(scores.qualifies(GameMode::Endless, None, 5));
+            scores.insert(entry("frog", GameMode::Endless, 5));
+        }
+
+        // A tie doesn't push anyone out.
+        assert!(!scores.qualifies(GameMode::Endless, None, 5));
+        assert!(scores.qualifies(GameMode::Endless, None, 6));
+        assert!(scores.qualifies(GameMode::TimeAttack, None, 1));
+    }
+
+    #[test]
+    fn insert_ranks_older_ties_higher() {
+        let mut scores = HighScores::default();
+        scores.insert(entry("other mode", GameMode::Daily, 1));
+        for i in 0..TABLE_SIZE {
+            scores.insert(entry(&i.to_string(), GameMode::Endless, 5));
+        }
+
+        scores.insert(entry("tie", GameMode::Endless, 5));
+        assert_eq!(
+            names(&scores, GameMode::Endless),
+            ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"]
+        );
+
+        scores.insert(entry("best", GameMode::Endless, 6));
+        assert_eq!(
+            names(&scores, GameMode::Endless),
+            ["best", "0", "1", "2", "3", "4", "5", "6", "7", "8"]
+        );
+        assert_eq!(names(&scores, GameMode::Daily), ["other mode"]);
+    }
+}
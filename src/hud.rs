@@ -0,0 +1,248 @@
+//! In-game overlay: score, run time, the frog's leaf and which neighbours are safe.
+//!
+//! Every element is only written to when what it shows changes.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::Stopwatch};
+
+use crate::{
+    leaf::{Leaf, SAFE_DECAY},
+    player::PlayerPos,
+    run_scope,
+    time_attack::TimeLeft,
+    GameAssets, GameMode, GameState, Score,
+};
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunClock>()
+            .add_system(spawn_hud.in_schedule(OnEnter(GameState::InGame)))
+            .add_systems(
+                (
+                    tick_clock,
+                    update_score,
+                    update_clock.after(tick_clock),
+                    update_leaf_meter,
+                    update_neighbours,
+                )
+                    .in_set(OnUpdate(GameState::InGame)),
+            );
+    }
+}
+
+const METER_WIDTH: f32 = 200.0;
+const CELL_SIZE: f32 = 18.0;
+const CELL_GAP: f32 = 3.0;
+/// Time left below which the time-attack countdown turns red.
+const HURRY_TIME: Duration = Duration::from_secs(10);
+
+/// Time spent in the current run, not counting pauses.
+#[derive(Resource, Default)]
+pub struct RunClock(pub Stopwatch);
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct ClockText;
+
+#[derive(Component)]
+struct LeafMeter;
+
+/// Shows whether the neighbour in this direction is safe to jump to.
+#[derive(Component)]
+struct NeighbourCell(IVec2);
+
+fn spawn_hud(mut commands: Commands, assets: Res<GameAssets>, mut clock: ResMut<RunClock>) {
+    clock.0.reset();
+
+    let style = TextStyle {
+        font: assets.font.clone(),
+        font_size: 40.0,
+        color: Color::SEA_GREEN,
+    };
+
+    commands.spawn((
+        TextBundle::from_section("Score: 0", style.clone()).with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                bottom: Val::Px(5.0),
+                right: Val::Px(15.0),
+                ..default()
+            },
+            ..default()
+        }),
+        ScoreText,
+        run_scope(),
+    ));
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(15.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                gap: Size::all(Val::Px(8.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(run_scope())
+        .with_children(|parent| {
+            parent.spawn((TextBundle::from_section("0:00", style), ClockText));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(METER_WIDTH), Val::Px(16.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0., 0., 0.3).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                                ..default()
+                            },
+                            background_color: Color::SEA_GREEN.into(),
+                            ..default()
+                        },
+                        LeafMeter,
+                    ));
+                });
+
+            // A cross of cells around the frog's own.
+            let side = 3. * CELL_SIZE + 2. * CELL_GAP;
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::all(Val::Px(side)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for dir in [IVec2::ZERO, IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+                        let offset = |d: i32| Val::Px((1 + d) as f32 * (CELL_SIZE + CELL_GAP));
+                        let mut cell = parent.spawn(NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                position: UiRect {
+                                    left: offset(dir.x),
+                                    top: offset(-dir.y),
+                                    ..default()
+                                },
+                                size: Size::all(Val::Px(CELL_SIZE)),
+                                ..default()
+                            },
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        });
+                        if dir != IVec2::ZERO {
+                            cell.insert(NeighbourCell(dir));
+                        }
+                    }
+                });
+        });
+}
+
+fn tick_clock(mut clock: ResMut<RunClock>, time: Res<Time>) {
+    clock.0.tick(time.delta());
+}
+
+fn update_score(score: Res<Score>, mut text: Query<&mut Text, With<ScoreText>>) {
+    if !score.is_changed() {
+        return;
+    }
+
+    text.single_mut().sections[0].value = format!("Score: {}", score.0);
+}
+
+/// `m:ss`
+pub fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Counts up, or down to the end of a time-attack run.
+fn update_clock(
+    clock: Res<RunClock>,
+    mode: Res<GameMode>,
+    left: Res<TimeLeft>,
+    mut text: Query<&mut Text, With<ClockText>>,
+) {
+    let (time, hurry) = match *mode {
+        // Rounded up, so that 0:00 only shows once the time is up.
+        GameMode::TimeAttack => (left.0 + Duration::from_millis(999), left.0 < HURRY_TIME),
+        GameMode::Endless | GameMode::Daily | GameMode::Puzzle | GameMode::Tutorial => {
+            (clock.0.elapsed(), false)
+        }
+    };
+    let value = format_time(time);
+    let color = if hurry {
+        Color::ORANGE_RED
+    } else {
+        Color::SEA_GREEN
+    };
+
+    let mut text = text.single_mut();
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+    if text.sections[0].style.color != color {
+        text.sections[0].style.color = color;
+    }
+}
+
+fn update_leaf_meter(
+    player_pos: Res<PlayerPos>,
+    leaves: Query<&Leaf>,
+    mut meter: Query<(&mut Style, &mut BackgroundColor), With<LeafMeter>>,
+) {
+    let decay = leaves
+        .iter()
+        .find(|leaf| leaf.pos == player_pos.0)
+        .map_or(1., |leaf| leaf.decay);
+    let width = Val::Percent(100. * (1. - decay));
+    let color = if decay < SAFE_DECAY {
+        Color::SEA_GREEN
+    } else {
+        Color::ORANGE_RED
+    };
+
+    let (mut style, mut background) = meter.single_mut();
+    if style.size.width != width {
+        style.size.width = width;
+    }
+    if background.0 != color {
+        background.0 = color;
+    }
+}
+
+fn update_neighbours(
+    player_pos: Res<PlayerPos>,
+    leaves: Query<&Leaf>,
+    mut cells: Query<(&NeighbourCell, &mut BackgroundColor)>,
+) {
+    for (cell, mut background) in &mut cells {
+        let pos = player_pos.0 + cell.0;
+        let color = match leaves.iter().find(|leaf| leaf.pos == pos) {
+            Some(leaf) if leaf.decay < SAFE_DECAY => Color::SEA_GREEN,
+            Some(_) => Color::ORANGE_RED,
+            None => Color::rgba(0., 0., 0., 0.3),
+        };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
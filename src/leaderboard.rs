@@ -0,0 +1,316 @@
+//! Shared leaderboards, set up with the `leaderboard` setting.
+//!
+//! Besides a RON file, e.g. on a network share, scores can go to a server speaking plain
+//! HTTP. `src/bin/leaderboard_server.rs` is a reference one:
+//!
+//! - `GET /top?mode=Endless&level=1&n=10` replies with the best scores as a RON list, `level`
+//!   being optional.
+//! - `POST /submit` takes a RON [`HighScore`].
+
+use std::{
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    daily::DailyRun,
+    highscore::{score_table, HighScore, HighScoreWindow, HighScores},
+    hud::RunClock,
+    puzzle::PuzzleRun,
+    settings::Settings,
+    stats::RunStats,
+    title::AttractMode,
+    GameMode, GameState, NoPersistence, RunSeed, Score,
+};
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rankings>()
+            .add_system(open_leaderboard)
+            .add_system(
+                submit_run
+                    .in_schedule(OnEnter(GameState::GameOver))
+                    .run_if(not(resource_exists::<AttractMode>()))
+                    .run_if(not(resource_exists::<NoPersistence>())),
+            )
+            .add_system(fetch_on_open.in_set(OnUpdate(GameState::Title)))
+            .add_system(poll_rankings)
+            .add_system(rankings_window.after(poll_rankings));
+    }
+}
+
+/// Scores shown in the online ranking.
+const RANKING_SIZE: usize = 10;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+pub trait Leaderboard: Send + Sync {
+    /// Adds `entry`, whose seed lets anyone replay the run to check it.
+    fn submit(&self, entry: &HighScore) -> Result<(), String>;
+
+    /// The best `n` scores of `mode` and `level`, best first.
+    fn top(&self, mode: GameMode, level: Option<u32>, n: usize) -> Result<Vec<HighScore>, String>;
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum LeaderboardConfig {
+    #[default]
+    None,
+    File(PathBuf),
+    /// `http://host:port` of a leaderboard server.
+    Http(String),
+}
+
+impl LeaderboardConfig {
+    pub fn open(&self) -> Option<Arc<dyn Leaderboard>> {
+        match self {
+            LeaderboardConfig::None => None,
+            LeaderboardConfig::File(path) => Some(Arc::new(FileLeaderboard::new(path.clone()))),
+            LeaderboardConfig::Http(url) => match HttpLeaderboard::new(url) {
+                Ok(board) => Some(Arc::new(board)),
+                Err(e) => {
+                    warn!("no leaderboard: {e}");
+                    None
+                }
+            },
+        }
+    }
+}
+
+/// Keeps every submitted score in a RON file.
+pub struct FileLeaderboard {
+    path: PathBuf,
+}
+
+impl FileLeaderboard {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn read(&self) -> Result<Vec<HighScore>, String> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => ron::from_str(&text).map_err(|e| e.to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+impl Leaderboard for FileLeaderboard {
+    fn submit(&self, entry: &HighScore) -> Result<(), String> {
+        let mut entries = self.read()?;
+        // After the entries with the same score, so that older ones rank higher.
+        let i = entries.partition_point(|other| other.score >= entry.score);
+        entries.insert(i, entry.clone());
+
+        let text = ron::ser::to_string_pretty(&entries, default()).map_err(|e| e.to_string())?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&self.path, text).map_err(|e| e.to_string())
+    }
+
+    fn top(&self, mode: GameMode, level: Option<u32>, n: usize) -> Result<Vec<HighScore>, String> {
+        let mut entries = self.read()?;
+        entries.retain(|entry| entry.mode == mode && entry.level == level);
+        entries.truncate(n);
+        Ok(entries)
+    }
+}
+
+/// Talks to a leaderboard server, see the module docs.
+pub struct HttpLeaderboard {
+    /// `host:port`
+    address: String,
+}
+
+impl HttpLeaderboard {
+    pub fn new(url: &str) -> Result<Self, String> {
+        let address = url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("only http:// leaderboards are supported, not {url}"))?;
+
+        Ok(Self {
+            address: address.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Sends a request and returns the body of a successful reply.
+    fn request(&self, method: &str, target: &str, body: &str) -> Result<String, String> {
+        let address = self
+            .address
+            .to_socket_addrs()
+            .map_err(|e| e.to_string())?
+            .next()
+            .ok_or_else(|| format!("no address for {}", self.address))?;
+        let mut stream =
+            TcpStream::connect_timeout(&address, TIMEOUT).map_err(|e| e.to_string())?;
+        stream
+            .set_read_timeout(Some(TIMEOUT))
+            .map_err(|e| e.to_string())?;
+
+        write!(
+            stream,
+            "{method} {target} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Content-Type: application/ron\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n\
+             {body}",
+            self.address,
+            body.len(),
+        )
+        .map_err(|e| e.to_string())?;
+
+        let mut reply = String::new();
+        stream
+            .read_to_string(&mut reply)
+            .map_err(|e| e.to_string())?;
+
+        let (head, body) = reply.split_once("\r\n\r\n").ok_or("malformed reply")?;
+        let status = head.lines().next().unwrap_or_default();
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => Ok(body.to_string()),
+            _ => Err(format!("server replied {status}: {body}")),
+        }
+    }
+}
+
+impl Leaderboard for HttpLeaderboard {
+    fn submit(&self, entry: &HighScore) -> Result<(), String> {
+        let body = ron::to_string(entry).map_err(|e| e.to_string())?;
+        self.request("POST", "/submit", &body).map(drop)
+    }
+
+    fn top(&self, mode: GameMode, level: Option<u32>, n: usize) -> Result<Vec<HighScore>, String> {
+        let mode = ron::to_string(&mode).map_err(|e| e.to_string())?;
+        let mut target = format!("/top?mode={mode}&n={n}");
+        if let Some(level) = level {
+            target += &format!("&level={level}");
+        }
+
+        let body = self.request("GET", &target, "")?;
+        ron::from_str(&body).map_err(|e| e.to_string())
+    }
+}
+
+type Job = Arc<Mutex<Option<Result<Vec<HighScore>, String>>>>;
+
+/// The configured leaderboard and what was last fetched from it.
+#[derive(Resource, Default)]
+struct Rankings {
+    board: Option<Arc<dyn Leaderboard>>,
+    /// `None` while loading.
+    shown: Option<Result<Vec<HighScore>, String>>,
+    job: Option<Job>,
+}
+
+impl Rankings {
+    /// Submits `entry`, if any, and then fetches the ranking of `mode` and `level`, on another
+    /// thread so that a slow server doesn't stall the game.
+    fn refresh(&mut self, mode: GameMode, level: Option<u32>, entry: Option<HighScore>) {
+        let Some(board) = self.board.clone() else { return };
+
+        let job = Job::default();
+        self.job = Some(job.clone());
+        self.shown = None;
+
+        std::thread::spawn(move || {
+            let result = entry
+                .map_or(Ok(()), |entry| board.submit(&entry))
+                .and_then(|()| board.top(mode, level, RANKING_SIZE));
+            *job.lock().unwrap() = Some(result);
+        });
+    }
+}
+
+fn open_leaderboard(settings: Res<Settings>, mut rankings: ResMut<Rankings>) {
+    if settings.is_changed() {
+        rankings.board = settings.leaderboard.open();
+    }
+}
+
+/// Submits every scored run, whether it made the local table or not, under the last name
+/// entered there.
+fn submit_run(
+    mut rankings: ResMut<Rankings>,
+    high_scores: Res<HighScores>,
+    score: Res<Score>,
+    clock: Res<RunClock>,
+    seed: Res<RunSeed>,
+    mode: Res<GameMode>,
+    puzzle: Res<PuzzleRun>,
+    stats: Res<RunStats>,
+    daily: Res<DailyRun>,
+) {
+    let level = puzzle.score_level(*mode);
+    let entry = (mode.has_scores() && daily.counts()).then(|| {
+        let name = match high_scores.last_name.as_str() {
+            "" => "Frog".to_string(),
+            name => name.to_string(),
+        };
+        HighScore::of_run(name, &score, &clock, &seed, *mode, level, &stats)
+    });
+    rankings.refresh(*mode, level, entry);
+}
+
+fn fetch_on_open(
+    window: Res<HighScoreWindow>,
+    mut rankings: ResMut<Rankings>,
+    mode: Res<GameMode>,
+    puzzle: Res<PuzzleRun>,
+) {
+    if (window.is_changed() || mode.is_changed() || puzzle.is_changed()) && window.0 {
+        rankings.refresh(*mode, puzzle.score_level(*mode), None);
+    }
+}
+
+fn poll_rankings(mut rankings: ResMut<Rankings>) {
+    let Some(job) = &rankings.job else { return };
+    let Some(result) = job.lock().unwrap().take() else { return };
+
+    if let Err(e) = &result {
+        warn!("leaderboard: {e}");
+    }
+    rankings.shown = Some(result);
+    rankings.job = None;
+}
+
+fn rankings_window(
+    mut egui_contexts: EguiContexts,
+    rankings: Res<Rankings>,
+    state: Res<State<GameState>>,
+    high_score_window: Res<HighScoreWindow>,
+    attract: Option<Res<AttractMode>>,
+) {
+    let shown = match state.0 {
+        GameState::GameOver => attract.is_none(),
+        GameState::Title => high_score_window.0,
+        _ => false,
+    };
+    if !shown || rankings.board.is_none() {
+        return;
+    }
+
+    egui::Window::new("Online ranking")
+        .anchor(egui::Align2::RIGHT_CENTER, [-10., 0.])
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_contexts.ctx_mut(), |ui| match &rankings.shown {
+            None => {
+                ui.label("Loading...");
+            }
+            Some(Err(e)) => {
+                ui.label(format!("Unavailable: {e}"));
+            }
+            Some(Ok(entries)) => score_table(ui, "online_ranking", entries),
+        });
+}
@@ -1,13 +1,16 @@
 use std::time::Duration;
 
-use crate::{GameState, InGameTag};
+use crate::{manifest, mixer::PlaySfx, GameRng, GameState};
 
 use super::Rotation;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
-use iyes_progress::prelude::AssetsLoading;
 
 pub const LEAF_SIZE: f32 = 256.0;
+/// Leaves below this decay are safe to jump to.
+pub const SAFE_DECAY: f32 = 0.4;
+/// Fastest a leaf turns, in radians per second.
+const MAX_SPIN: f32 = 0.2 * std::f32::consts::PI;
 
 pub struct LeafPlugin;
 
@@ -37,18 +40,9 @@ pub struct LeafAsset {
 
 impl FromWorld for LeafAsset {
     fn from_world(world: &mut World) -> Self {
-        let assets = world.get_resource::<AssetServer>().unwrap();
-
-        let texture = assets.load("leaf.png");
-        let audio_drop = assets.load("water_drop.ogg");
-
-        let mut loading = world.get_resource_mut::<AssetsLoading>().unwrap();
-        loading.add(texture.clone());
-        loading.add(audio_drop.clone());
-
         LeafAsset {
-            texture,
-            audio_drop,
+            texture: manifest::load(world, "leaf"),
+            audio_drop: manifest::load(world, "sfx.water_drop"),
         }
     }
 }
@@ -57,13 +51,17 @@ pub fn spawn_leaf<'w, 's, 'a>(
     commands: &'a mut Commands<'w, 's>,
     pos: IVec2,
     asset: &LeafAsset,
+    rng: &mut GameRng,
 ) -> bevy::ecs::system::EntityCommands<'w, 's, 'a> {
     let tr = Vec2::new(pos.x as f32, pos.y as f32) * Vec2::splat(LEAF_SIZE);
+    let rotation = Rotation(rng.f32() * (2. * std::f32::consts::PI));
+    let spin = rng.f32() * MAX_SPIN;
     let mut e = commands.spawn(LeafBundle {
         leaf: Leaf {
             decay: 0.,
             pos,
             restore_timer: None,
+            spin,
         },
         sprite: SpriteBundle {
             texture: asset.texture.clone(),
@@ -73,7 +71,7 @@ pub fn spawn_leaf<'w, 's, 'a>(
             },
             ..default()
         },
-        rotation: Rotation(fastrand::f32() * (2. * std::f32::consts::PI)),
+        rotation,
     });
     e.insert((
         Sensor,
@@ -81,15 +79,16 @@ pub fn spawn_leaf<'w, 's, 'a>(
         CollisionGroups::new(Group::GROUP_4, Group::ALL),
         ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC,
     ));
-    e.insert(InGameTag);
     e
 }
 
 #[derive(Component, Reflect)]
 pub struct Leaf {
     pub decay: f32,
-    pos: IVec2,
+    pub pos: IVec2,
     restore_timer: Option<Timer>,
+    /// How fast the leaf turns, in radians per second.
+    spin: f32,
 }
 
 #[derive(Bundle)]
@@ -102,14 +101,12 @@ pub struct LeafBundle {
 
 fn leaf_decay_system(
     player_pos: Res<crate::player::PlayerPos>,
-    mut leaf: Query<(&mut Leaf, &mut Sprite)>,
+    mut leaf: Query<(&mut Leaf, &mut Sprite, &GlobalTransform)>,
     time: Res<Time>,
-    audio: Res<Audio>,
     asset: Res<LeafAsset>,
+    mut sfx: EventWriter<PlaySfx>,
 ) {
-    let mut leaf_drop = false;
-
-    leaf.for_each_mut(|(mut x, mut sprite)| {
+    leaf.for_each_mut(|(mut x, mut sprite, transform)| {
         if let Some(timer) = x.restore_timer.as_mut() {
             timer.tick(time.delta());
 
@@ -130,7 +127,11 @@ fn leaf_decay_system(
             };
             x.decay = (x.decay + dd * time.delta_seconds()).clamp(0., 1.);
             if pre < 0.8 && x.decay >= 0.8 {
-                leaf_drop = true;
+                sfx.send(
+                    PlaySfx::new(asset.audio_drop.clone())
+                        .at(transform.translation().truncate())
+                        .with_random_pitch(0.1),
+                );
             }
             if x.restore_timer.is_none() && x.decay >= 1.0 {
                 x.restore_timer = Some(Timer::new(Duration::from_secs(5), TimerMode::Once));
@@ -140,27 +141,10 @@ fn leaf_decay_system(
         let g = 1.0 - 0.9 * (10. * x.decay).powi(2) / 100.;
         sprite.color = Color::rgba(g, g, g, g);
     });
-
-    if leaf_drop {
-        audio.play_with_settings(
-            asset.audio_drop.clone(),
-            PlaybackSettings::ONCE.with_speed(1.0 + (fastrand::f32() - 0.5) * 0.2),
-        );
-    }
 }
 
-fn leaf_rotator(mut q: Query<(Entity, &mut Rotation), With<Leaf>>, time: Res<Time>) {
-    q.for_each_mut(|(e, mut r)| {
-        let xorshift = |mut n: u32| {
-            n ^= 2463534242;
-            n ^= n << 13;
-            n ^= n >> 17;
-            n ^= n << 5;
-            n
-        };
-        r.0 += std::f32::consts::PI
-            * time.delta_seconds()
-            * (xorshift(e.index()) as f32 / std::u32::MAX as f32 * 20.0)
-            / 100.0;
+fn leaf_rotator(mut q: Query<(&Leaf, &mut Rotation)>, time: Res<Time>) {
+    q.for_each_mut(|(leaf, mut r)| {
+        r.0 += leaf.spin * time.delta_seconds();
     })
 }
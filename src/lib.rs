@@ -0,0 +1,390 @@
+#![allow(clippy::forget_non_drop)]
+
+use bevy::prelude::*;
+// use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use bevy_rapier2d::prelude::*;
+use iyes_progress::ProgressPlugin;
+use serde::{Deserialize, Serialize};
+use std::f32;
+
+mod achievements;
+pub mod autopilot;
+mod daily;
+mod definitions;
+pub mod enemy;
+pub mod env;
+mod gameover;
+pub mod highscore;
+mod hud;
+pub mod leaderboard;
+mod leaf;
+mod loading;
+mod minimap;
+mod manifest;
+mod mixer;
+mod pause;
+mod persist;
+mod player;
+mod puzzle;
+pub mod screen_transition;
+mod score;
+mod settings;
+mod skins;
+mod tension;
+pub mod state_scoped;
+pub mod state_transition;
+pub mod stats;
+mod time_attack;
+mod title;
+mod tutorial;
+
+use leaf::LeafAsset;
+use mixer::PlayMusic;
+use state_scoped::DespawnOnExit;
+
+/// The windowed game: [`DefaultPlugins`] plus [`LeafrogPlugin`].
+pub struct GamePlugin;
+
+/// Game logic without the window, renderer and audio backends, so it can also run headless.
+pub struct LeafrogPlugin;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, States)]
+enum GameState {
+    #[default]
+    AssetLoading,
+    Title,
+    InGame,
+    GameOver,
+}
+
+/// Rules of the current run. High scores are kept per mode.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Endless,
+    /// Eat as many bugs as possible before the time is up. Drowning costs time, not the run.
+    TimeAttack,
+    /// Endless, in the pond of the day.
+    Daily,
+    /// Handcrafted levels with a goal each, rated with stars instead of scores.
+    Puzzle,
+    /// Step-by-step prompts teaching the controls and leaf decay.
+    Tutorial,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 5] = [
+        GameMode::Endless,
+        GameMode::TimeAttack,
+        GameMode::Daily,
+        GameMode::Puzzle,
+        GameMode::Tutorial,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Endless => "Endless",
+            GameMode::TimeAttack => "Time attack",
+            GameMode::Daily => "Daily challenge",
+            GameMode::Puzzle => "Puzzles",
+            GameMode::Tutorial => "Tutorial",
+        }
+    }
+
+    /// Whether runs go into the high-score tables and leaderboards.
+    pub fn has_scores(self) -> bool {
+        match self {
+            GameMode::Endless | GameMode::TimeAttack | GameMode::Daily => true,
+            // Puzzles are rated with stars instead, and the tutorial isn't rated.
+            GameMode::Puzzle | GameMode::Tutorial => false,
+        }
+    }
+
+    pub fn drowning_ends_run(self) -> bool {
+        match self {
+            GameMode::Endless | GameMode::Daily | GameMode::Puzzle => true,
+            GameMode::TimeAttack | GameMode::Tutorial => false,
+        }
+    }
+}
+
+/// Lifetime of everything that belongs to a run, which stays on screen through game over.
+fn run_scope() -> DespawnOnExit<GameState> {
+    DespawnOnExit::new(GameState::InGame).carry_into(GameState::GameOver)
+}
+
+// #[derive(PhysicsLayer)]
+// enum CollisionLayer {
+//     Enemy,
+//     Tongue,
+//     Player,
+//     Leaf,
+// }
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(Color::CYAN))
+            .add_plugins(DefaultPlugins)
+            .add_plugin(LeafrogPlugin);
+    }
+}
+
+impl Plugin for LeafrogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(bevy_egui::EguiPlugin)
+            // .add_plugin(WorldInspectorPlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.))
+            .add_state::<GameState>()
+            .add_plugin(
+                ProgressPlugin::new(GameState::AssetLoading)
+                    .continue_to(GameState::Title)
+                    .track_assets(),
+            )
+            .add_plugin(state_transition::StateTransitionDetectorPlugin::<GameState>::default())
+            .add_plugin(state_scoped::StateScopedPlugin::<GameState>::default());
+
+        // Settings go first, the asset manifest reads its packs from them.
+        app.add_plugin(settings::SettingsPlugin)
+            .add_plugin(definitions::DefinitionsPlugin)
+            .add_plugin(mixer::MixerPlugin)
+            .add_plugin(tension::TensionPlugin)
+            .add_plugin(loading::LoadingPlugin)
+            .add_plugin(enemy::EnemyPlugin)
+            .add_plugin(player::PlayerPlugin)
+            .add_plugin(leaf::LeafPlugin)
+            .add_plugin(title::TitlePlugin)
+            .add_plugin(gameover::GameOverPlugin)
+            .add_plugin(autopilot::AutopilotPlugin)
+            .add_plugin(pause::PausePlugin)
+            .add_plugin(screen_transition::ScreenTransitionPlugin)
+            .add_plugin(score::ScorePlugin)
+            .add_plugin(stats::StatsPlugin)
+            .add_plugin(hud::HudPlugin)
+            .add_plugin(highscore::HighScorePlugin)
+            .add_plugin(leaderboard::LeaderboardPlugin)
+            .add_plugin(minimap::MinimapPlugin)
+            .add_plugin(achievements::AchievementsPlugin)
+            .add_plugin(skins::SkinsPlugin)
+            .add_plugin(time_attack::TimeAttackPlugin)
+            .add_plugin(daily::DailyPlugin)
+            .add_plugin(puzzle::PuzzlePlugin)
+            .add_plugin(tutorial::TutorialPlugin)
+            .register_type::<player::Player>()
+            .register_type::<Rotation>()
+            .add_startup_system(startup);
+
+        // In game, Esc opens the pause menu instead, and on game over it returns to the title.
+        app.add_system(
+            bevy::window::close_on_esc
+                .run_if(not(in_state(GameState::InGame)))
+                .run_if(not(in_state(GameState::GameOver))),
+        )
+            .init_resource::<MousePos>()
+            .init_resource::<GameAssets>()
+            .init_resource::<GameRng>()
+            .init_resource::<Score>()
+            .init_resource::<GameMode>()
+            .init_resource::<RunSeed>()
+            .add_system(
+                record_run_seed
+                    .in_base_set(CoreSet::StateTransitions)
+                    .after(daily::start_daily)
+                    .before(apply_state_transition::<GameState>),
+            )
+            .add_system(my_cursor_system)
+            .add_system(rotation_system)
+            .add_system(ingame_startup.in_schedule(OnEnter(GameState::InGame)))
+            .add_system(fade_out_music.in_schedule(OnExit(GameState::InGame)))
+            .add_system(
+                restart_control
+                    .in_set(OnUpdate(GameState::InGame))
+                    .run_if(not(resource_exists::<title::AttractMode>())),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct GameAssets {
+    /// Stems of the in-game music, see [`PlayMusic`].
+    music: Vec<Handle<AudioSource>>,
+    font: Handle<Font>,
+}
+
+impl FromWorld for GameAssets {
+    fn from_world(world: &mut World) -> Self {
+        // Extra stems are optional, asset packs can add them.
+        let mut music = vec![manifest::load(world, "music.bgm")];
+        let stems = (1..).map_while(|i| manifest::load_optional(world, &format!("music.bgm.{i}")));
+        music.extend(stems);
+
+        GameAssets {
+            music,
+            font: manifest::load(world, "font.main"),
+        }
+    }
+}
+
+fn startup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default()).insert(MainCamera);
+}
+
+/// Random number generator for everything that affects gameplay, so that a run can be
+/// reproduced from its seed. Purely cosmetic randomness (e.g. sound pitch) doesn't use it.
+#[derive(Resource)]
+pub struct GameRng {
+    state: u64,
+}
+
+impl GameRng {
+    pub fn with_seed(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns a random `f32` in range `0..1`.
+    pub fn f32(&mut self) -> f32 {
+        let rng = fastrand::Rng::with_seed(self.state);
+        let value = rng.f32();
+        self.state = rng.get_seed();
+        value
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::with_seed(fastrand::u64(..))
+    }
+}
+
+/// [`GameRng`] state at the start of the current run, which reproduces it.
+#[derive(Resource, Default)]
+pub struct RunSeed(pub u64);
+
+/// Keeps runs that aren't a player's, such as those of [`env::LeafrogEnv`], from unlocking
+/// achievements or saving anything in the user data directory.
+#[derive(Resource)]
+pub struct NoPersistence;
+
+/// Runs right before the transition, so that nothing has used the generator in the new run yet.
+fn record_run_seed(
+    next_state: Res<NextState<GameState>>,
+    rng: Res<GameRng>,
+    mut seed: ResMut<RunSeed>,
+) {
+    if next_state.0 == Some(GameState::InGame) {
+        seed.0 = rng.state;
+    }
+}
+
+#[derive(Resource, Default)]
+struct Score(u32);
+
+fn ingame_startup(
+    mut commands: Commands,
+    leaf_asset: Res<LeafAsset>,
+    mut rng: ResMut<GameRng>,
+    mut score: ResMut<Score>,
+    mut music: EventWriter<PlayMusic>,
+    assets: Res<GameAssets>,
+    mode: Res<GameMode>,
+) {
+    info!("ingame_startup");
+
+    *score = Score(0);
+    music.send(PlayMusic(assets.music.clone()));
+
+    // Puzzles and the tutorial bring their own pond, see `puzzle::Level::spawn_leaves`.
+    if matches!(*mode, GameMode::Puzzle | GameMode::Tutorial) {
+        return;
+    }
+
+    let leaf_pos = [
+        [0, 0],
+        [0, 1],
+        [1, 0],
+        [0, -1],
+        [-1, 0],
+        [-1, -1],
+        [1, 1],
+        [-1, 1],
+        [1, -1],
+        [0, -2],
+        [0, 2],
+        [2, 0],
+        [-2, 0],
+    ];
+
+    let mut leaves = vec![];
+    for p in leaf_pos {
+        leaves.push(
+            leaf::spawn_leaf(&mut commands, IVec2::new(p[0], p[1]), &leaf_asset, &mut rng).id(),
+        );
+    }
+
+    commands
+        .spawn((Name::new("Leafs"), SpatialBundle::default()))
+        .insert(run_scope())
+        .push_children(&leaves);
+}
+
+fn fade_out_music(mut music: EventWriter<PlayMusic>) {
+    music.send(PlayMusic(Vec::new()));
+}
+
+/// Entering `InGame` again from itself restarts the run, see [`run_scope`].
+fn restart_control(keys: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.just_pressed(KeyCode::R) {
+        next_state.set(GameState::InGame);
+    }
+}
+
+#[derive(Component, Default, Reflect)]
+pub struct Rotation(pub f32);
+
+fn rotation_system(mut q: Query<(&mut Transform, &Rotation), Changed<Rotation>>) {
+    q.for_each_mut(|(mut tr, r)| {
+        tr.rotation = Quat::from_rotation_z(r.0);
+    });
+}
+
+#[derive(Component)]
+struct MainCamera;
+
+#[derive(Default, Debug, Resource)]
+struct MousePos(Option<Vec2>);
+
+fn my_cursor_system(
+    // need to get window dimensions
+    windows: Query<&Window>,
+    mut cursor_evr: EventReader<CursorMoved>,
+    // query to get camera transform
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut mouse_pos: ResMut<MousePos>,
+) {
+    let Some(cursor_moved) = cursor_evr.iter().last() else { return };
+    let screen_pos = cursor_moved.position;
+
+    // get the camera info and transform
+    // assuming there is exactly one main camera entity, so query::single() is OK
+    let (camera, camera_transform) = camera.single();
+
+    let wnd = windows.single();
+
+    // get the size of the window
+    let window_size = Vec2::new(wnd.width(), wnd.height());
+
+    // convert screen position [0..resolution] to ndc [-1..1] (gpu coordinates)
+    let ndc = (screen_pos / window_size) * 2.0 - Vec2::ONE;
+
+    // matrix for undoing the projection and camera transform
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+
+    // use it to convert ndc to world-space coordinates
+    let world_pos = ndc_to_world.project_point3(ndc.extend(-1.0));
+
+    // reduce it to a 2D value
+    let new_mouse_pos = world_pos.truncate();
+
+    if mouse_pos.0 != Some(new_mouse_pos) {
+        mouse_pos.0 = Some(new_mouse_pos);
+    }
+}
@@ -0,0 +1,161 @@
+//! Loading screen shown while [`GameState::AssetLoading`] waits for `iyes_progress`.
+
+use bevy::{
+    asset::{Asset, HandleId, LoadState},
+    prelude::*,
+};
+use iyes_progress::prelude::{AssetsLoading, ProgressCounter};
+
+use crate::{state_scoped::DespawnOnExit, GameAssets, GameState};
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingAssets>()
+            .add_system(setup_loading.in_schedule(OnEnter(GameState::AssetLoading)))
+            .add_systems(
+                (detect_failures, update_bar, update_text)
+                    .chain()
+                    .in_set(OnUpdate(GameState::AssetLoading)),
+            );
+    }
+}
+
+const TIPS: [&str; 4] = [
+    "Leaves sink while you stand on them. Keep hopping!",
+    "A sunk leaf comes back after a while.",
+    "Click to catch bugs with your tongue.",
+    "You can't shoot your tongue mid-jump.",
+];
+const TIP_SECONDS: f32 = 4.0;
+
+/// Assets tracked by `iyes_progress`, kept here as well to find out which ones failed.
+#[derive(Resource, Default)]
+pub struct LoadingAssets {
+    handles: Vec<HandleId>,
+    pub failed: Vec<String>,
+}
+
+/// Registers `handle` to be loaded before leaving [`GameState::AssetLoading`].
+pub fn track<T: Asset>(world: &mut World, handle: &Handle<T>) {
+    world
+        .resource_mut::<AssetsLoading>()
+        .add(handle.clone());
+    world
+        .get_resource_or_insert_with(LoadingAssets::default)
+        .handles
+        .push(handle.id());
+}
+
+#[derive(Component)]
+struct ProgressBar;
+
+#[derive(Component)]
+struct LoadingText;
+
+fn setup_loading(mut commands: Commands, game_assets: Res<GameAssets>) {
+    let style = TextStyle {
+        font: game_assets.font.clone(),
+        font_size: 30.0,
+        color: Color::SEA_GREEN,
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(DespawnOnExit::new(GameState::AssetLoading))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(400.), Val::Px(24.)),
+                        margin: UiRect::bottom(Val::Px(20.)),
+                        ..default()
+                    },
+                    background_color: Color::DARK_GRAY.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                                ..default()
+                            },
+                            background_color: Color::SEA_GREEN.into(),
+                            ..default()
+                        },
+                        ProgressBar,
+                    ));
+                });
+
+            parent.spawn((
+                TextBundle::from_section("", style).with_text_alignment(TextAlignment::Center),
+                LoadingText,
+            ));
+        });
+}
+
+/// `iyes_progress` waits for failed assets forever, so look for them here.
+fn detect_failures(server: Res<AssetServer>, mut loading: ResMut<LoadingAssets>) {
+    let LoadingAssets { handles, failed } = &mut *loading;
+
+    handles.retain(|&handle| {
+        if server.get_load_state(handle) != LoadState::Failed {
+            return true;
+        }
+
+        let path = server
+            .get_handle_path(handle)
+            .map_or_else(|| format!("{handle:?}"), |p| p.path().display().to_string());
+        error!("failed to load asset: {path}");
+        failed.push(path);
+        false
+    });
+}
+
+fn update_bar(
+    counter: Option<Res<ProgressCounter>>,
+    loading: Res<LoadingAssets>,
+    mut bar: Query<(&mut Style, &mut BackgroundColor), With<ProgressBar>>,
+) {
+    let Some(counter) = counter else { return };
+    let progress = counter.progress();
+    let ratio = progress.done as f32 / progress.total.max(1) as f32;
+
+    let (mut style, mut color) = bar.single_mut();
+    style.size.width = Val::Percent(100. * ratio);
+    if !loading.failed.is_empty() {
+        *color = Color::CRIMSON.into();
+    }
+}
+
+fn update_text(
+    loading: Res<LoadingAssets>,
+    time: Res<Time>,
+    mut text: Query<&mut Text, With<LoadingText>>,
+) {
+    let value = if loading.failed.is_empty() {
+        let tip = (time.elapsed_seconds() / TIP_SECONDS) as usize % TIPS.len();
+        TIPS[tip].to_string()
+    } else {
+        format!(
+            "Failed to load:\n{}\n\nPress Esc to quit.",
+            loading.failed.join("\n")
+        )
+    };
+
+    let mut text = text.single_mut();
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
@@ -0,0 +1,178 @@
+//! Maps logical asset ids to files, see `assets/manifest.ron`.
+//!
+//! An asset pack is a directory `assets/packs/<name>/` with its own `manifest.ron`, whose
+//! paths are relative to the pack. Packs listed in [`Settings::asset_packs`] override the
+//! entries of the base manifest, and of the packs before them.
+//!
+//! The web build has no file system to look at, so it only knows the base manifest, built in,
+//! and finds out about missing files when they fail to load.
+
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::asset::FileAssetIo;
+use bevy::{
+    asset::Asset,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{loading, settings::Settings};
+
+const MANIFEST: &str = "manifest.ron";
+
+/// Asset paths by id, relative to the asset folder.
+#[derive(Resource, Debug)]
+pub struct AssetManifest {
+    #[cfg(not(target_arch = "wasm32"))]
+    root: PathBuf,
+    paths: HashMap<String, String>,
+}
+
+impl AssetManifest {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read(packs: &[String]) -> Self {
+        let root = FileAssetIo::get_base_path().join("assets");
+        let mut paths = read_entries(&root.join(MANIFEST)).unwrap_or_default();
+
+        for pack in packs {
+            let dir = format!("packs/{pack}");
+            let Some(entries) = read_entries(&root.join(&dir).join(MANIFEST)) else { continue };
+            for (id, path) in entries {
+                paths.insert(id, format!("{dir}/{path}"));
+            }
+        }
+
+        Self { root, paths }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read(_packs: &[String]) -> Self {
+        let paths = ron::from_str(include_str!("../assets/manifest.ron")).unwrap_or_else(|e| {
+            warn!("ignoring {MANIFEST}: {e}");
+            default()
+        });
+
+        Self { paths }
+    }
+
+    /// The path of asset `id`, if it's listed and its file exists.
+    fn path(&self, id: &str) -> Option<&str> {
+        let Some(path) = self.paths.get(id) else {
+            warn!("asset `{id}` is not in the manifest");
+            return None;
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        if !self.root.join(path).is_file() {
+            warn!("asset `{id}` is missing: {path}");
+            return None;
+        }
+        Some(path)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_entries(path: &Path) -> Option<HashMap<String, String>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| warn!("can't read {}: {e}", path.display()))
+        .ok()?;
+
+    ron::from_str(&text)
+        .map_err(|e| warn!("ignoring {}: {e}", path.display()))
+        .ok()
+}
+
+/// An asset type that can stand in for a missing file.
+pub trait Placeholder: Asset + Sized {
+    fn placeholder() -> Option<Self>;
+}
+
+impl Placeholder for Image {
+    /// A magenta square, so that it's easy to spot.
+    fn placeholder() -> Option<Self> {
+        Some(Image::new_fill(
+            Extent3d {
+                width: 64,
+                height: 64,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[255, 0, 255, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        ))
+    }
+}
+
+impl Placeholder for AudioSource {
+    fn placeholder() -> Option<Self> {
+        Some(AudioSource {
+            bytes: silent_wav().into(),
+        })
+    }
+}
+
+/// Fonts can't be made up, so text in a missing font isn't drawn at all.
+impl Placeholder for Font {
+    fn placeholder() -> Option<Self> {
+        None
+    }
+}
+
+/// A tenth of a second of 8-bit mono silence.
+fn silent_wav() -> Vec<u8> {
+    const RATE: u32 = 8000;
+    const LEN: u32 = RATE / 10;
+
+    let mut wav = Vec::with_capacity(44 + LEN as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + LEN).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // channels
+    wav.extend_from_slice(&RATE.to_le_bytes());
+    wav.extend_from_slice(&RATE.to_le_bytes()); // bytes per second
+    wav.extend_from_slice(&1u16.to_le_bytes()); // bytes per frame
+    wav.extend_from_slice(&8u16.to_le_bytes()); // bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&LEN.to_le_bytes());
+    // 8-bit samples are unsigned, centered on 128.
+    wav.resize(44 + LEN as usize, 128);
+    wav
+}
+
+fn init_manifest(world: &mut World) {
+    if !world.contains_resource::<AssetManifest>() {
+        let manifest = AssetManifest::read(&world.resource::<Settings>().asset_packs);
+        world.insert_resource(manifest);
+    }
+}
+
+/// Loads asset `id` and tracks it for the loading screen. If its file is missing, a
+/// placeholder is used instead, so the game still runs with a partial asset checkout.
+pub fn load<T: Placeholder>(world: &mut World, id: &str) -> Handle<T> {
+    init_manifest(world);
+
+    if let Some(path) = world.resource::<AssetManifest>().path(id).map(str::to_owned) {
+        let handle = world.resource::<AssetServer>().load(path);
+        loading::track(world, &handle);
+        return handle;
+    }
+
+    match T::placeholder() {
+        Some(asset) => world.resource_mut::<Assets<T>>().add(asset),
+        None => Handle::default(),
+    }
+}
+
+/// Like [`load`], but returns `None` without complaint if `id` isn't in the manifest.
+pub fn load_optional<T: Placeholder>(world: &mut World, id: &str) -> Option<Handle<T>> {
+    init_manifest(world);
+
+    if !world.resource::<AssetManifest>().paths.contains_key(id) {
+        return None;
+    }
+    Some(load(world, id))
+}
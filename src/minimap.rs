@@ -0,0 +1,224 @@
+//! Corner map of the whole pond: leaves by decay, the frog and the bugs. Toggled with M.
+
+use bevy::prelude::*;
+
+use crate::{
+    enemy::Bug,
+    leaf::{Leaf, LEAF_SIZE},
+    player::PlayerPos,
+    run_scope,
+    title::AttractMode,
+    GameState,
+};
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MinimapVisible>()
+            .add_system(spawn_minimap.in_schedule(OnEnter(GameState::InGame)))
+            .add_system(
+                toggle_minimap
+                    .in_set(OnUpdate(GameState::InGame))
+                    .run_if(not(resource_exists::<AttractMode>())),
+            )
+            .add_systems(
+                (show_minimap, track_new, update_dots)
+                    .chain()
+                    .in_set(OnUpdate(GameState::InGame)),
+            );
+    }
+}
+
+/// Side of the map on screen, in pixels.
+const MAP_SIZE: f32 = 180.0;
+const BUG_DOT: f32 = 4.0;
+const FROG_DOT: f32 = 8.0;
+
+#[derive(Resource)]
+pub struct MinimapVisible(pub bool);
+
+impl Default for MinimapVisible {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+#[derive(Component)]
+struct Minimap;
+
+/// Draws the leaf or bug `0` on the map.
+#[derive(Component)]
+struct MinimapDot(Entity);
+
+#[derive(Component)]
+struct MinimapFrog;
+
+fn spawn_minimap(mut commands: Commands, visible: Res<MinimapVisible>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..default()
+                },
+                size: Size::all(Val::Px(MAP_SIZE)),
+                overflow: Overflow::Hidden,
+                ..default()
+            },
+            background_color: Color::rgba(0., 0.2, 0.3, 0.5).into(),
+            visibility: visibility(visible.0),
+            ..default()
+        })
+        .insert((Minimap, run_scope()))
+        .with_children(|parent| {
+            parent.spawn((
+                dot_bundle(FROG_DOT, Color::LIME_GREEN),
+                ZIndex::Local(2),
+                MinimapFrog,
+            ));
+        });
+}
+
+fn dot_bundle(size: f32, color: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            size: Size::all(Val::Px(size)),
+            ..default()
+        },
+        background_color: color.into(),
+        ..default()
+    }
+}
+
+fn visibility(visible: bool) -> Visibility {
+    if visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+fn toggle_minimap(keys: Res<Input<KeyCode>>, mut visible: ResMut<MinimapVisible>) {
+    if keys.just_pressed(KeyCode::M) {
+        visible.0 = !visible.0;
+    }
+}
+
+fn show_minimap(visible: Res<MinimapVisible>, mut map: Query<&mut Visibility, With<Minimap>>) {
+    if !visible.is_changed() {
+        return;
+    }
+
+    for mut vis in &mut map {
+        *vis = visibility(visible.0);
+    }
+}
+
+fn track_new(
+    mut commands: Commands,
+    map: Query<Entity, With<Minimap>>,
+    leaves: Query<Entity, Added<Leaf>>,
+    bugs: Query<Entity, Added<Bug>>,
+) {
+    let Ok(map) = map.get_single() else { return };
+
+    commands.entity(map).with_children(|parent| {
+        for leaf in &leaves {
+            parent.spawn((dot_bundle(0., Color::NONE), MinimapDot(leaf)));
+        }
+        for bug in &bugs {
+            parent.spawn((
+                dot_bundle(BUG_DOT, Color::BLACK),
+                ZIndex::Local(1),
+                MinimapDot(bug),
+            ));
+        }
+    });
+}
+
+/// Maps world positions into the map, fitting the whole pond.
+struct Projection {
+    center: Vec2,
+    scale: f32,
+}
+
+impl Projection {
+    fn fit(leaves: &Query<&Leaf>) -> Self {
+        let (min, max) = leaves.iter().fold(
+            (IVec2::splat(i32::MAX), IVec2::splat(i32::MIN)),
+            |(min, max), leaf| (min.min(leaf.pos), max.max(leaf.pos)),
+        );
+        if min.x > max.x {
+            return Self {
+                center: Vec2::ZERO,
+                scale: 1.,
+            };
+        }
+
+        // Half a leaf of margin on each side.
+        let extent = (max - min + IVec2::ONE).max_element() as f32 * LEAF_SIZE;
+        Self {
+            center: (min + max).as_vec2() * LEAF_SIZE / 2.,
+            scale: MAP_SIZE / extent,
+        }
+    }
+
+    /// Top left corner of a dot of `size` at `world`, kept inside the map.
+    fn place(&self, world: Vec2, size: f32) -> UiRect {
+        let map = (world - self.center) * self.scale + Vec2::splat(MAP_SIZE / 2.);
+        let corner = (map - Vec2::splat(size / 2.)).clamp(Vec2::ZERO, Vec2::splat(MAP_SIZE - size));
+        UiRect {
+            left: Val::Px(corner.x),
+            // The map's y axis points down.
+            top: Val::Px(MAP_SIZE - size - corner.y),
+            ..default()
+        }
+    }
+}
+
+fn update_dots(
+    mut commands: Commands,
+    player_pos: Res<PlayerPos>,
+    all_leaves: Query<&Leaf>,
+    bugs: Query<&GlobalTransform, With<Bug>>,
+    mut dots: Query<(Entity, &MinimapDot, &mut Style, &mut BackgroundColor)>,
+    mut frog: Query<&mut Style, (With<MinimapFrog>, Without<MinimapDot>)>,
+) {
+    let projection = Projection::fit(&all_leaves);
+    let leaf_dot = LEAF_SIZE * projection.scale * 0.8;
+
+    for (entity, dot, mut style, mut background) in &mut dots {
+        let (position, size, color) = if let Ok(leaf) = all_leaves.get(dot.0) {
+            let pos = leaf.pos.as_vec2() * LEAF_SIZE;
+            let color = (Color::SEA_GREEN * (1. - leaf.decay) + Color::CRIMSON * leaf.decay)
+                .with_a(1. - leaf.decay * 0.7);
+            (projection.place(pos, leaf_dot), leaf_dot, color)
+        } else if let Ok(bug) = bugs.get(dot.0) {
+            let pos = bug.translation().truncate();
+            (projection.place(pos, BUG_DOT), BUG_DOT, background.0)
+        } else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        if style.position != position {
+            style.position = position;
+        }
+        if style.size.width != Val::Px(size) {
+            style.size = Size::all(Val::Px(size));
+        }
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+
+    let position = projection.place(player_pos.0.as_vec2() * LEAF_SIZE, FROG_DOT);
+    let mut style = frog.single_mut();
+    if style.position != position {
+        style.position = position;
+    }
+}
@@ -0,0 +1,285 @@
+//! Plays all sounds, on a music and an SFX bus whose volumes come from [`Settings`].
+//!
+//! Sound effects are spatial: they pan and fade with their distance from the camera.
+
+use std::time::Duration;
+
+use bevy::{asset::HandleId, math::Affine3A, prelude::*};
+
+use crate::{settings::Settings, tension::Tension, MainCamera};
+
+pub struct MixerPlugin;
+
+impl Plugin for MixerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Mixer>()
+            .add_event::<PlayMusic>()
+            .add_event::<PlaySfx>()
+            .add_systems((play_music, play_sfx, mix).chain());
+    }
+}
+
+/// How long music takes to fade in or out.
+const MUSIC_FADE: Duration = Duration::from_secs(1);
+/// Music volume, relative to the bus, while a ducking sound plays.
+const DUCK_LEVEL: f32 = 0.35;
+/// How fast the music follows ducking, in volume per second.
+const DUCK_SPEED: f32 = 4.0;
+/// How many copies of the same sound may play at once.
+const MAX_COPIES: usize = 3;
+/// Sounds closer to the camera than this, in pixels on screen, play at full volume.
+const HEARING_RADIUS: f32 = 700.0;
+/// Distance between the ears, relative to [`HEARING_RADIUS`]. Larger pans harder.
+const EAR_GAP: f32 = 1.0;
+/// How long a voice waits for its sink before it's assumed there is no audio output.
+const SINK_WAIT: Duration = Duration::from_secs(5);
+
+/// Crossfades to a looped track made of stems, or fades the music out if there are none.
+///
+/// The first stem always plays. The others come in one after another as [`Tension`] rises.
+pub struct PlayMusic(pub Vec<Handle<AudioSource>>);
+
+pub struct PlaySfx {
+    pub sound: Handle<AudioSource>,
+    /// Where the sound comes from in the world, or `None` for the centre of the screen.
+    pub position: Option<Vec2>,
+    pub speed: f32,
+    /// Turns the music down while the sound plays.
+    pub duck: bool,
+}
+
+impl PlaySfx {
+    pub fn new(sound: Handle<AudioSource>) -> Self {
+        Self {
+            sound,
+            position: None,
+            speed: 1.0,
+            duck: false,
+        }
+    }
+
+    pub fn at(mut self, position: Vec2) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Varies the speed, and so the pitch, randomly by up to `amount` either way.
+    pub fn with_random_pitch(mut self, amount: f32) -> Self {
+        self.speed = 1.0 + (fastrand::f32() - 0.5) * 2. * amount;
+        self
+    }
+
+    pub fn ducking(mut self) -> Self {
+        self.duck = true;
+        self
+    }
+}
+
+struct MusicVoice {
+    sink: Handle<AudioSink>,
+    stem: usize,
+    stems: usize,
+    /// Fade level in `0..=1`.
+    level: f32,
+    fading_out: bool,
+    /// Time since the track was started.
+    age: Duration,
+}
+
+struct SfxVoice {
+    sound: HandleId,
+    sink: Handle<SpatialAudioSink>,
+    duck: bool,
+    /// Time since the sound was started.
+    age: Duration,
+}
+
+#[derive(Resource)]
+pub struct Mixer {
+    music: Vec<MusicVoice>,
+    sfx: Vec<SfxVoice>,
+    /// Current ducking factor of the music.
+    duck: f32,
+    paused: bool,
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self {
+            music: Vec::new(),
+            sfx: Vec::new(),
+            duck: 1.0,
+            paused: false,
+        }
+    }
+}
+
+impl Mixer {
+    /// Pauses all sounds, along with fades and ducking.
+    pub fn pause(&mut self, sinks: &Assets<AudioSink>, spatial_sinks: &Assets<SpatialAudioSink>) {
+        self.paused = true;
+        self.for_each_sink(sinks, spatial_sinks, |sink| sink.pause());
+    }
+
+    pub fn resume(&mut self, sinks: &Assets<AudioSink>, spatial_sinks: &Assets<SpatialAudioSink>) {
+        self.paused = false;
+        self.for_each_sink(sinks, spatial_sinks, |sink| sink.play());
+    }
+
+    fn for_each_sink(
+        &self,
+        sinks: &Assets<AudioSink>,
+        spatial_sinks: &Assets<SpatialAudioSink>,
+        f: impl Fn(&dyn AudioSinkPlayback),
+    ) {
+        for voice in &self.music {
+            if let Some(sink) = sinks.get(&voice.sink) {
+                f(sink);
+            }
+        }
+        for voice in &self.sfx {
+            if let Some(sink) = spatial_sinks.get(&voice.sink) {
+                f(sink);
+            }
+        }
+    }
+}
+
+fn play_music(
+    mut events: EventReader<PlayMusic>,
+    mut mixer: ResMut<Mixer>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    for PlayMusic(stems) in events.iter() {
+        mixer
+            .music
+            .iter_mut()
+            .for_each(|voice| voice.fading_out = true);
+
+        // Started in the same frame, so they stay in sync.
+        for (i, stem) in stems.iter().enumerate() {
+            let settings = PlaybackSettings::LOOP.with_volume(0.);
+            let sink = audio.play_with_settings(stem.clone(), settings);
+            mixer.music.push(MusicVoice {
+                sink: audio_sinks.get_handle(sink),
+                stem: i,
+                stems: stems.len(),
+                level: 0.,
+                fading_out: false,
+                age: Duration::ZERO,
+            });
+        }
+    }
+}
+
+fn play_sfx(
+    mut events: EventReader<PlaySfx>,
+    mut mixer: ResMut<Mixer>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<SpatialAudioSink>>,
+    settings: Res<Settings>,
+    camera: Query<&GlobalTransform, With<MainCamera>>,
+) {
+    // Sounds are placed relative to the camera, which is the listener, in units of
+    // `HEARING_RADIUS`. Camera zoom is taken into account, so this is what's on screen.
+    let to_camera = camera
+        .get_single()
+        .map_or(Affine3A::IDENTITY, |camera| camera.affine().inverse());
+    let listener = Transform::IDENTITY;
+
+    for ev in events.iter() {
+        let id = ev.sound.id();
+        if mixer.sfx.iter().filter(|voice| voice.sound == id).count() >= MAX_COPIES {
+            continue;
+        }
+
+        let emitter = ev.position.map_or(Vec3::ZERO, |pos| {
+            let relative = to_camera.transform_point3(pos.extend(0.));
+            relative.truncate().extend(0.) / HEARING_RADIUS
+        });
+        let sink = audio.play_spatial_with_settings(
+            ev.sound.clone(),
+            PlaybackSettings::ONCE
+                .with_volume(settings.sfx_volume)
+                .with_speed(ev.speed),
+            listener,
+            EAR_GAP,
+            emitter,
+        );
+        mixer.sfx.push(SfxVoice {
+            sound: id,
+            sink: audio_sinks.get_handle(sink),
+            duck: ev.duck,
+            age: Duration::ZERO,
+        });
+    }
+}
+
+fn mix(
+    mut mixer: ResMut<Mixer>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    spatial_sinks: Res<Assets<SpatialAudioSink>>,
+    settings: Res<Settings>,
+    tension: Res<Tension>,
+    time: Res<Time>,
+) {
+    if mixer.paused {
+        return;
+    }
+
+    let Mixer {
+        music, sfx, duck, ..
+    } = &mut *mixer;
+    let dt = time.delta();
+
+    sfx.retain_mut(|voice| {
+        voice.age += dt;
+        match spatial_sinks.get(&voice.sink) {
+            Some(sink) => !sink.empty(),
+            None => voice.age < SINK_WAIT,
+        }
+    });
+
+    let ducked = sfx.iter().any(|voice| voice.duck);
+    let target = if ducked { DUCK_LEVEL } else { 1. };
+    let step = DUCK_SPEED * dt.as_secs_f32();
+    *duck = if *duck < target {
+        (*duck + step).min(target)
+    } else {
+        (*duck - step).max(target)
+    };
+
+    let step = dt.as_secs_f32() / MUSIC_FADE.as_secs_f32();
+    music.retain_mut(|voice| {
+        let delta = if voice.fading_out { -step } else { step };
+        voice.level = (voice.level + delta).clamp(0., 1.);
+        voice.age += dt;
+
+        // The sink shows up once the audio output has started playing the track.
+        let sink = audio_sinks.get(&voice.sink);
+        if voice.fading_out && voice.level == 0. {
+            match sink {
+                Some(sink) => sink.stop(),
+                // Otherwise the track would still start later, and loop on out of reach.
+                None if voice.age < SINK_WAIT => return true,
+                None => {}
+            }
+            return false;
+        }
+
+        if let Some(sink) = sink {
+            let volume = voice.level * stem_gain(voice.stem, voice.stems, tension.0);
+            sink.set_volume(settings.music_volume * volume * *duck);
+        }
+        true
+    });
+}
+
+/// Stems above the first one fade in over equal slices of the tension range.
+fn stem_gain(stem: usize, stems: usize, tension: f32) -> f32 {
+    if stem == 0 {
+        return 1.;
+    }
+    (tension * (stems - 1) as f32 - (stem - 1) as f32).clamp(0., 1.)
+}
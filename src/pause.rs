@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_rapier2d::prelude::*;
+use bevy_tweening::{Animator, AnimatorState};
+
+use crate::{
+    mixer::Mixer, player::Tongue, settings::SettingsWindow, title::AttractMode, GameState,
+};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<PauseState>()
+            // Everything that makes the game progress runs in `OnUpdate(GameState::InGame)`.
+            .configure_set(OnUpdate(GameState::InGame).run_if(in_state(PauseState::Running)))
+            .add_system(
+                toggle_pause
+                    .run_if(in_state(GameState::InGame))
+                    .run_if(not(resource_exists::<AttractMode>())),
+            )
+            .add_system(
+                pause_menu
+                    .run_if(in_state(GameState::InGame))
+                    .run_if(in_state(PauseState::Paused)),
+            )
+            .add_systems(
+                (
+                    pause_animators::<Transform>,
+                    pause_animators::<Handle<Image>>,
+                    pause_animators::<Tongue>,
+                    pause_animators::<Text>,
+                    pause_world,
+                )
+                    .in_schedule(OnEnter(PauseState::Paused)),
+            )
+            .add_systems(
+                (
+                    resume_animators::<Transform>,
+                    resume_animators::<Handle<Image>>,
+                    resume_animators::<Tongue>,
+                    resume_animators::<Text>,
+                    resume_world,
+                    close_settings,
+                )
+                    .in_schedule(OnExit(PauseState::Paused)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, States)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+fn toggle_pause(
+    keys: Res<Input<KeyCode>>,
+    state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+    mut settings_window: ResMut<SettingsWindow>,
+) {
+    if !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+
+    // Back to the pause menu first.
+    if settings_window.0 {
+        settings_window.0 = false;
+    } else {
+        next_state.set(match state.0 {
+            PauseState::Running => PauseState::Paused,
+            PauseState::Paused => PauseState::Running,
+        });
+    }
+}
+
+fn pause_menu(
+    mut egui_contexts: EguiContexts,
+    mut next_pause: ResMut<NextState<PauseState>>,
+    mut next_game: ResMut<NextState<GameState>>,
+    mut settings_window: ResMut<SettingsWindow>,
+) {
+    if settings_window.0 {
+        return;
+    }
+
+    egui::Window::new("Paused")
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            if ui.button("Resume").clicked() {
+                next_pause.set(PauseState::Running);
+            }
+            if ui.button("Restart").clicked() {
+                next_pause.set(PauseState::Running);
+                next_game.set(GameState::InGame);
+            }
+            if ui.button("Settings").clicked() {
+                settings_window.0 = true;
+            }
+            if ui.button("Quit to title").clicked() {
+                next_pause.set(PauseState::Running);
+                next_game.set(GameState::Title);
+            }
+        });
+}
+
+/// The settings window is shown in any state, it mustn't stay over the resumed game.
+fn close_settings(mut settings_window: ResMut<SettingsWindow>) {
+    settings_window.0 = false;
+}
+
+fn pause_animators<T: Component>(mut q: Query<&mut Animator<T>>) {
+    q.for_each_mut(|mut animator| animator.state = AnimatorState::Paused);
+}
+
+fn resume_animators<T: Component>(mut q: Query<&mut Animator<T>>) {
+    q.for_each_mut(|mut animator| animator.state = AnimatorState::Playing);
+}
+
+fn pause_world(
+    mut rapier: ResMut<RapierConfiguration>,
+    mut mixer: ResMut<Mixer>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    spatial_sinks: Res<Assets<SpatialAudioSink>>,
+) {
+    rapier.physics_pipeline_active = false;
+    mixer.pause(&audio_sinks, &spatial_sinks);
+}
+
+fn resume_world(
+    mut rapier: ResMut<RapierConfiguration>,
+    mut mixer: ResMut<Mixer>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    spatial_sinks: Res<Assets<SpatialAudioSink>>,
+) {
+    rapier.physics_pipeline_active = true;
+    mixer.resume(&audio_sinks, &spatial_sinks);
+}
@@ -0,0 +1,39 @@
+//! Progress kept between sessions, as RON files in the user data directory.
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+fn path(file: &str) -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("leafrog").join(file))
+}
+
+/// Reads `file`, starting over if it's missing or broken.
+pub fn load_ron<T: DeserializeOwned + Default>(file: &str) -> T {
+    let Some(path) = path(file) else { return default() };
+    let Ok(text) = std::fs::read_to_string(&path) else { return default() };
+
+    ron::from_str(&text).unwrap_or_else(|e| {
+        warn!("ignoring {}: {e}", path.display());
+        default()
+    })
+}
+
+pub fn save_ron<T: Serialize>(file: &str, value: &T) {
+    let Some(path) = path(file) else {
+        warn!("no data directory to save {file} to");
+        return;
+    };
+
+    let result = ron::ser::to_string_pretty(value, default())
+        .map_err(|e| e.to_string())
+        .and_then(|text| {
+            std::fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+            std::fs::write(&path, text).map_err(|e| e.to_string())
+        });
+
+    if let Err(e) = result {
+        error!("failed to save {}: {e}", path.display());
+    }
+}
@@ -2,16 +2,20 @@ use bevy::{prelude::*, sprite::Anchor};
 use bevy_egui::EguiContexts;
 use bevy_rapier2d::prelude::*;
 use bevy_tweening::*;
-use iyes_progress::prelude::AssetsLoading;
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::f32;
 
 use crate::enemy::EnemyKillEvent;
+use crate::manifest;
+use crate::mixer::PlaySfx;
+use crate::settings::Settings;
+use crate::skins::Skinned;
 use crate::state_transition::StateTransitionEvent;
-use crate::{GameState, InGameTag, MainCamera};
+use crate::{run_scope, GameMode, GameState, MainCamera};
 
 use super::Rotation;
-use crate::leaf::{Leaf, LEAF_SIZE};
+use crate::leaf::{Leaf, LEAF_SIZE, SAFE_DECAY};
 
 pub struct PlayerPlugin;
 
@@ -24,34 +28,36 @@ impl Plugin for PlayerPlugin {
             .register_type::<Tongue>()
             .init_resource::<PlayerAssets>()
             .init_resource::<PlayerPos>()
+            .init_resource::<JumpsLeft>()
+            .init_resource::<CameraShake>()
             .add_event::<LandingEvent>()
+            .add_event::<ShootTongueEvent>()
+            .add_event::<TongueShotEvent>()
+            .add_event::<DrownEvent>()
             .add_system(startup.in_schedule(OnEnter(GameState::InGame)))
             .add_systems(
                 (
                     camera_transform_system,
-                    jump_system,
-                    tongue_system,
+                    jump_system.in_set(PlayerControlSet),
+                    tongue_system.in_set(PlayerControlSet),
                     tongue_kill_system,
                     detect_drown,
                 )
                     .in_set(OnUpdate(GameState::InGame)),
             )
-            .add_system(remove_animators.in_schedule(OnExit(GameState::InGame)));
+            .add_system(reset_camera.in_schedule(OnExit(GameState::InGame)));
     }
 }
 
-fn remove_animators(
+/// The camera outlives the run, so it has to be undone by hand.
+fn reset_camera(
     mut commands: Commands,
-    player: Query<Entity, With<Player>>,
-    camera: Query<Entity, With<MainCamera>>,
+    mut camera: Query<(Entity, &mut Transform), With<MainCamera>>,
 ) {
-    commands
-        .entity(player.single())
-        .remove::<Animator<Transform>>()
-        .remove::<Animator<Handle<Image>>>();
-    commands
-        .entity(camera.single())
-        .remove::<Animator<Transform>>();
+    let (camera, mut camera_transform) = camera.single_mut();
+    commands.entity(camera).remove::<Animator<Transform>>();
+    // The run may end mid-jump, with the camera still zoomed.
+    camera_transform.scale = Vec3::ONE;
 }
 
 fn startup(mut commands: Commands, assets: Res<PlayerAssets>, mut player_pos: ResMut<PlayerPos>) {
@@ -62,6 +68,7 @@ fn startup(mut commands: Commands, assets: Res<PlayerAssets>, mut player_pos: Re
             texture: assets.player[0].clone(),
             transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
             sprite: Sprite {
+                color: assets.tint,
                 anchor: Anchor::Custom(Vec2::new(0.0, (64. - 50.) / 64.)),
                 ..default()
             },
@@ -72,7 +79,7 @@ fn startup(mut commands: Commands, assets: Res<PlayerAssets>, mut player_pos: Re
 
     let player = commands
         .spawn(frog)
-        .insert(Name::new("Player"))
+        .insert((Name::new("Player"), Skinned::Frog))
         .insert(InputManagerBundle::<PlayerAction> {
             action_state: ActionState::default(),
             input_map: InputMap::new([
@@ -95,15 +102,27 @@ fn startup(mut commands: Commands, assets: Res<PlayerAssets>, mut player_pos: Re
             //     .with_group(CollisionLayer::Player)
             //     .with_mask(CollisionLayer::Leaf),
         ))
-        .insert(InGameTag)
+        .insert(run_scope())
         .id();
 
     spawn_tongue(commands, player, assets);
 }
 
+/// Systems that act on [`PlayerAction`]s and [`ShootTongueEvent`]s.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerControlSet;
+
 #[derive(Debug, Default, Resource)]
 pub struct PlayerPos(pub IVec2);
 
+/// Jumps the frog has left in the run, if they're limited.
+#[derive(Debug, Default, Resource)]
+pub struct JumpsLeft(pub Option<u32>);
+
+/// Time left to shake the camera for.
+#[derive(Default, Resource)]
+struct CameraShake(f32);
+
 #[derive(Component, Default, Reflect)]
 pub struct Player {
     jumping: bool,
@@ -120,44 +139,53 @@ pub struct PlayerBundle {
 
 #[derive(Resource)]
 pub struct PlayerAssets {
+    /// Frames of the selected skin, see [`crate::skins`].
     pub player: [Handle<Image>; 3],
-    tongue_base: Handle<Image>,
-    tongue_tip: Handle<Image>,
+    pub tongue_base: Handle<Image>,
+    pub tongue_tip: Handle<Image>,
+    pub tint: Color,
     kill_sound: Handle<AudioSource>,
 }
 
 impl FromWorld for PlayerAssets {
     fn from_world(world: &mut World) -> Self {
-        let assets = world.get_resource::<AssetServer>().unwrap();
-
-        let player = [
-            assets.load("kaeru0.png"),
-            assets.load("kaeru1.png"),
-            assets.load("kaeru2.png"),
-        ];
-
-        let tongue_base = assets.load("tong_base.png");
+        PlayerAssets {
+            player: [
+                manifest::load(world, "frog.0"),
+                manifest::load(world, "frog.1"),
+                manifest::load(world, "frog.2"),
+            ],
+            tongue_base: manifest::load(world, "tongue.base"),
+            tongue_tip: manifest::load(world, "tongue.tip"),
+            tint: Color::WHITE,
+            kill_sound: manifest::load(world, "sfx.kill"),
+        }
+    }
+}
 
-        let tongue_tip = assets.load("tong_tip.png");
+/// The frog landed on the cell `0`.
+pub struct LandingEvent(pub IVec2);
 
-        let kill_sound = assets.load("syuwan.ogg");
+/// Shoots the tongue towards a world position, as a left click does.
+pub struct ShootTongueEvent(pub Vec2);
 
-        let mut loading = world.get_resource_mut::<AssetsLoading>().unwrap();
-        player.iter().for_each(|p| loading.add(p.clone()));
-        loading.add(tongue_base.clone());
-        loading.add(tongue_tip.clone());
-        loading.add(kill_sound.clone());
+/// The tongue went out, whether it catches anything or not.
+pub struct TongueShotEvent;
 
-        PlayerAssets {
-            player,
-            tongue_base,
-            tongue_tip,
-            kill_sound,
-        }
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeathCause {
+    /// Jumped where there was no leaf.
+    Water,
+    /// Stayed on a leaf until it sank.
+    SunkLeaf,
 }
 
-struct LandingEvent;
+/// The frog drowned.
+pub struct DrownEvent {
+    pub cause: DeathCause,
+    /// Whether it ends the run, see [`GameMode::drowning_ends_run`].
+    pub fatal: bool,
+}
 
 #[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug)]
 pub enum PlayerAction {
@@ -198,14 +226,25 @@ fn camera_transform_system(
     mut transform: Query<&mut Transform>,
     player: Query<Entity, With<Player>>,
     camera: Query<Entity, (With<Camera>, With<MainCamera>)>,
+    mut shake: ResMut<CameraShake>,
+    time: Res<Time>,
 ) {
     const CAMERA_Z: f32 = 999.9;
+    const SHAKE_AMPLITUDE: f32 = 6.0;
 
     let mut player_translation = transform.get(player.single()).unwrap().translation;
     player_translation.z = CAMERA_Z;
 
+    let mut offset = Vec3::ZERO;
+    if shake.0 > 0.0 {
+        shake.0 -= time.delta_seconds();
+        offset = Vec3::new(fastrand::f32() - 0.5, fastrand::f32() - 0.5, 0.0)
+            * 2.0
+            * SHAKE_AMPLITUDE;
+    }
+
     transform.get_mut(camera.single()).unwrap().translation =
-        player_translation / Vec3::new(2.0, 2.0, 1.0);
+        player_translation / Vec3::new(2.0, 2.0, 1.0) + offset;
 }
 
 fn jump_system(
@@ -216,6 +255,8 @@ fn jump_system(
     game_assets: Res<PlayerAssets>,
     mut pos: ResMut<PlayerPos>,
     mut landing: EventWriter<LandingEvent>,
+    settings: Res<Settings>,
+    mut jumps_left: ResMut<JumpsLeft>,
 ) {
     let (player_entity, mut player, player_action, player_transform) = player.single_mut();
     let camera_entity = camera.single_mut();
@@ -226,11 +267,12 @@ fn jump_system(
 
             *pos = PlayerPos(player.next_pos);
 
-            landing.send(LandingEvent);
+            landing.send(LandingEvent(pos.0));
         }
     }
 
     let jump = !player.jumping
+        && jumps_left.0 != Some(0)
         && (player_action.pressed(PlayerAction::Up)
             || player_action.pressed(PlayerAction::Down)
             || player_action.pressed(PlayerAction::Left)
@@ -239,6 +281,9 @@ fn jump_system(
     if jump {
         // This can be local
         player.jumping = true;
+        if let Some(left) = &mut jumps_left.0 {
+            *left -= 1;
+        }
 
         let rot;
         if player_action.pressed(PlayerAction::Up) {
@@ -281,7 +326,9 @@ fn jump_system(
             },
         ));
 
-        commands.entity(camera_entity).insert(Animator::new(tween));
+        if settings.camera_zoom {
+            commands.entity(camera_entity).insert(Animator::new(tween));
+        }
 
         let tween = Tracks::new([
             Tween::new(
@@ -351,6 +398,8 @@ pub struct Tongue {
     base: Entity,
     tip: Entity,
     extending: bool,
+    /// Bugs caught since the tongue was last shot.
+    kills: u32,
 }
 
 #[derive(Bundle)]
@@ -375,6 +424,7 @@ impl TongueBundle {
                 base,
                 tip,
                 extending: false,
+                kills: 0,
             },
             rotation: default(),
             visibility: VisibilityBundle {
@@ -399,8 +449,7 @@ fn spawn_tongue(mut commands: Commands, parent: Entity, res: Res<PlayerAssets>)
             },
             ..default()
         })
-        .insert(Name::new("TongueBase"))
-        .insert(InGameTag)
+        .insert((Name::new("TongueBase"), Skinned::TongueBase))
         .id();
     let tip = commands
         .spawn(SpriteBundle {
@@ -422,14 +471,12 @@ fn spawn_tongue(mut commands: Commands, parent: Entity, res: Res<PlayerAssets>)
             //     .with_group(CollisionLayer::Tongue)
             //     .with_mask(CollisionLayer::Enemy),
         ))
-        .insert(Name::new("TongueTip"))
-        .insert(InGameTag)
+        .insert((Name::new("TongueTip"), Skinned::TongueTip))
         .id();
 
     let tongue = commands
         .spawn(TongueBundle::new(base, tip))
         .push_children(&[base, tip])
-        .insert(InGameTag)
         .id();
 
     commands.entity(parent).add_child(tongue);
@@ -448,25 +495,43 @@ impl lens::Lens<Tongue> for TongueLengthLens {
 
 fn tongue_kill_system(
     mut ev_kill: EventWriter<EnemyKillEvent>,
-    tongue: Query<(&Tongue, &Visibility)>,
+    mut tongue: Query<(&mut Tongue, &Visibility)>,
+    transforms: Query<&GlobalTransform>,
     rapier_ctx: Res<RapierContext>,
     game_assets: Res<PlayerAssets>,
-    audio: Res<Audio>,
+    mut sfx: EventWriter<PlaySfx>,
+    settings: Res<Settings>,
+    mut shake: ResMut<CameraShake>,
 ) {
-    let (tongue, tongue_vis) = tongue.single();
+    let (mut tongue, tongue_vis) = tongue.single_mut();
 
     if tongue.extending && tongue_vis != Visibility::Hidden {
+        // The tip is gone if the run is being restarted.
+        let Ok(tip) = transforms.get(tongue.tip) else { return };
+        let tip = tip.translation().truncate();
+
         let mut killed = false;
         for other in get_intersections(&rapier_ctx, tongue.tip) {
-            ev_kill.send(EnemyKillEvent(other));
+            tongue.kills += 1;
+            ev_kill.send(EnemyKillEvent {
+                bug: other,
+                position: tip,
+                shot_kills: tongue.kills,
+            });
             killed = true;
         }
 
         if killed {
-            audio.play_with_settings(
-                game_assets.kill_sound.clone(),
-                PlaybackSettings::ONCE.with_speed(1.0 + (fastrand::f32() - 0.5) * 0.2),
+            sfx.send(
+                PlaySfx::new(game_assets.kill_sound.clone())
+                    .at(tip)
+                    .with_random_pitch(0.1)
+                    .ducking(),
             );
+
+            if settings.screen_shake {
+                shake.0 = 0.15;
+            }
         }
     }
 }
@@ -479,6 +544,8 @@ fn tongue_system(
     buttons: Res<Input<MouseButton>>,
     mouse_pos: Res<super::MousePos>,
     mut reader: EventReader<TweenCompleted>,
+    mut shoot: EventReader<ShootTongueEvent>,
+    mut shot: EventWriter<TongueShotEvent>,
     mut egui_contexts: EguiContexts,
 ) {
     let (tongue_entity, mut tongue, g_tr, mut visibility) = tongue.single_mut();
@@ -512,16 +579,18 @@ fn tongue_system(
         }
     }
 
-    if egui_contexts.ctx_mut().is_pointer_over_area() {
-        return;
+    let mut target = shoot.iter().last().map(|ev| ev.0);
+
+    if !egui_contexts.ctx_mut().is_pointer_over_area() && buttons.just_pressed(MouseButton::Left) {
+        target = mouse_pos.0.or(target);
     }
 
-    if buttons.just_pressed(MouseButton::Left) && !player.jumping && !tongue.extending {
-        *visibility = Visibility::Inherited;
+    let Some(target) = target else { return };
 
-        let Some(mouse_pos) = mouse_pos.0 else { return };
+    if !player.jumping && !tongue.extending {
+        *visibility = Visibility::Inherited;
 
-        let length = mouse_pos.distance(g_tr.translation().truncate()) - 32.0;
+        let length = target.distance(g_tr.translation().truncate()) - 32.0;
 
         let tween = Tween::new(
             EaseFunction::QuarticInOut,
@@ -536,38 +605,74 @@ fn tongue_system(
         commands.entity(tongue_entity).insert(Animator::new(tween));
 
         tongue.extending = true;
+        tongue.kills = 0;
+        shot.send(TongueShotEvent);
 
         let player_rot = transform.get(player_entity).unwrap().rotation;
 
-        let to_mouse = mouse_pos - g_tr.translation().truncate();
+        let to_target = target - g_tr.translation().truncate();
 
         // player rotation will be applied, hence multiplication by the inverse of it
         transform.get_mut(tongue_entity).unwrap().rotation =
-            Quat::from_rotation_z(Vec2::Y.angle_between(to_mouse)) * player_rot.inverse();
+            Quat::from_rotation_z(Vec2::Y.angle_between(to_target)) * player_rot.inverse();
     }
 }
 
 fn detect_drown(
     landing: EventReader<LandingEvent>,
-    q: Query<(Entity, &Player)>,
+    mut q: Query<(Entity, &mut Player, &mut Transform)>,
     leafs: Query<&Leaf>,
     rapier_ctx: Res<RapierContext>,
-    tran: EventReader<StateTransitionEvent<GameState>>,
+    mut tran: EventReader<StateTransitionEvent<GameState>>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut drown: EventWriter<DrownEvent>,
+    mode: Res<GameMode>,
+    mut pos: ResMut<PlayerPos>,
+    mut drowned: Local<bool>,
 ) {
-    let (player_entity, player) = q.single();
+    let (player_entity, mut player, mut transform) = q.single_mut();
 
-    if player.jumping && landing.is_empty() {
+    // Physics only sees the colliders spawned on entering the run from the next frame on.
+    let entered = tran.iter().any(|ev| ev.to == GameState::InGame);
+    if entered {
+        *drowned = false;
+    }
+    // Once is enough while the run is ending.
+    if *drowned || player.jumping && landing.is_empty() {
         return;
     }
 
     let inter = get_intersections(&rapier_ctx, player_entity).collect::<Vec<_>>();
 
-    if inter.iter().any(|&e| leafs.get(e).unwrap().decay >= 1.0)
-        || (inter.is_empty() && tran.is_empty())
-    {
+    let cause = if inter.iter().any(|&e| leafs.get(e).unwrap().decay >= 1.0) {
+        DeathCause::SunkLeaf
+    } else if inter.is_empty() && !entered {
+        DeathCause::Water
+    } else {
+        return;
+    };
+
+    let fatal = mode.drowning_ends_run();
+    drown.send(DrownEvent { cause, fatal });
+    if fatal {
+        *drowned = true;
         next_state.set(GameState::GameOver);
+        return;
     }
+
+    // Back onto the nearest safe leaf, or the least sunk one if none is safe.
+    let distance = |leaf: &Leaf| (leaf.pos - player.next_pos).as_vec2().length();
+    let nearest_safe = leafs
+        .iter()
+        .filter(|leaf| leaf.decay < SAFE_DECAY)
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)));
+    let least_sunk = || leafs.iter().min_by(|a, b| a.decay.total_cmp(&b.decay));
+    let Some(leaf) = nearest_safe.or_else(least_sunk) else { return };
+
+    player.next_pos = leaf.pos;
+    *pos = PlayerPos(leaf.pos);
+    let z = transform.translation.z;
+    transform.translation = (leaf.pos.as_vec2() * LEAF_SIZE).extend(z);
 }
 
 fn get_intersections(ctx: &RapierContext, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
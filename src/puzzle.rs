@@ -0,0 +1,494 @@
+//! [`GameMode::Puzzle`]: handcrafted levels from `assets/puzzles.ron`, or an asset pack's, each
+//! with a goal, rated with up to three stars by the jumps it took.
+//!
+//! Levels unlock one after the other, and the best rating of each is saved in the user data
+//! directory.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    definitions::{self, Definitions},
+    enemy::{self, BugPath, EnemyAssets},
+    hud::format_time,
+    leaf::{self, Leaf, LeafAsset, LEAF_SIZE},
+    manifest, persist,
+    player::{JumpsLeft, LandingEvent},
+    run_scope,
+    stats::{self, RunStats},
+    title::AttractMode,
+    GameAssets, GameMode, GameRng, GameState, NoPersistence,
+};
+
+pub struct PuzzlePlugin;
+
+impl Plugin for PuzzlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Puzzles>()
+            .init_resource::<PuzzleRun>()
+            .init_resource::<LevelSelect>()
+            .add_system(read_definitions.in_schedule(OnExit(GameState::AssetLoading)))
+            .add_system(limit_jumps.in_schedule(OnEnter(GameState::InGame)))
+            .add_system(
+                spawn_level
+                    .in_schedule(OnEnter(GameState::InGame))
+                    .run_if(resource_equals(GameMode::Puzzle)),
+            )
+            .add_system(
+                check_goal
+                    .in_set(OnUpdate(GameState::InGame))
+                    .after(stats::count_jumps)
+                    .run_if(resource_equals(GameMode::Puzzle)),
+            )
+            .add_system(
+                next_level
+                    .in_set(OnUpdate(GameState::GameOver))
+                    .run_if(resource_equals(GameMode::Puzzle)),
+            )
+            .add_system(level_select.in_set(OnUpdate(GameState::Title)));
+    }
+}
+
+const DEFINITIONS: &str = "puzzles";
+const PROGRESS: &str = "puzzles.ron";
+
+/// See `assets/puzzles.ron`.
+#[derive(Debug, Clone, Deserialize)]
+pub enum Goal {
+    EatAll,
+    ReachFlower { cell: (i32, i32), seconds: f32 },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BugDefinition {
+    /// In leaves.
+    path: Vec<(f32, f32)>,
+    /// In leaves per second.
+    #[serde(default)]
+    speed: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Level {
+    pub name: String,
+    leaves: Vec<(i32, i32)>,
+    #[serde(default)]
+    bugs: Vec<BugDefinition>,
+    goal: Goal,
+    #[serde(default)]
+    max_jumps: Option<u32>,
+    /// Most jumps for three and two stars.
+    stars: (u32, u32),
+}
+
+impl Level {
+    fn stars(&self, jumps: u32) -> u32 {
+        if jumps <= self.stars.0 {
+            3
+        } else if jumps <= self.stars.1 {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn describe(&self) -> String {
+        let mut goal = match self.goal {
+            Goal::EatAll => format!("Eat all {} bugs", self.bugs.len()),
+            Goal::ReachFlower { seconds, .. } => format!("Reach the flower within {seconds}s"),
+        };
+        if let Some(jumps) = self.max_jumps {
+            goal += &format!(" in at most {jumps} jumps");
+        }
+        goal
+    }
+
+    fn flower(&self) -> Option<IVec2> {
+        match self.goal {
+            Goal::EatAll => None,
+            Goal::ReachFlower { cell, .. } => Some(IVec2::from(cell)),
+        }
+    }
+
+    /// Spawns the pond of the level, with the flower if there is one.
+    pub fn spawn_leaves(&self, commands: &mut Commands, leaf_asset: &LeafAsset, rng: &mut GameRng) {
+        let mut leaves = vec![];
+        for &cell in &self.leaves {
+            let pos = IVec2::from(cell);
+            let mut leaf = leaf::spawn_leaf(commands, pos, leaf_asset, rng);
+            if self.flower() == Some(pos) {
+                leaf.insert(Flower).with_children(|parent| {
+                    parent.spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::PINK,
+                            custom_size: Some(Vec2::splat(48.)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0., 0., 0.5),
+                        ..default()
+                    });
+                });
+            }
+            leaves.push(leaf.id());
+        }
+
+        commands
+            .spawn((Name::new("Leafs"), SpatialBundle::default()))
+            .insert(run_scope())
+            .push_children(&leaves);
+    }
+
+    pub fn spawn_bugs(&self, commands: &mut Commands, enemy_assets: &EnemyAssets) {
+        for bug in &self.bugs {
+            let points = bug
+                .path
+                .iter()
+                .map(|&(x, y)| Vec2::new(x, y) * LEAF_SIZE)
+                .collect();
+            enemy::spawn_path_bug(
+                commands,
+                enemy_assets,
+                BugPath::new(points, bug.speed * LEAF_SIZE),
+            );
+        }
+    }
+}
+
+/// What is kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Progress {
+    /// Best rating by level index.
+    stars: BTreeMap<usize, u32>,
+}
+
+#[derive(Resource)]
+pub struct Puzzles {
+    source: Handle<Definitions>,
+    /// Empty until the assets are loaded.
+    levels: Vec<Level>,
+    progress: Progress,
+}
+
+impl FromWorld for Puzzles {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            source: manifest::load(world, DEFINITIONS),
+            levels: Vec::new(),
+            progress: persist::load_ron(PROGRESS),
+        }
+    }
+}
+
+impl Puzzles {
+    pub fn level(&self, level: usize) -> Option<&Level> {
+        self.levels.get(level)
+    }
+
+    /// Stars of the best solution of `level`, if it was solved.
+    pub fn best(&self, level: usize) -> Option<u32> {
+        self.progress.stars.get(&level).copied()
+    }
+
+    /// Levels open up once the one before is solved.
+    pub fn is_unlocked(&self, level: usize) -> bool {
+        level < self.levels.len() && (level == 0 || self.best(level - 1).is_some())
+    }
+
+    /// Keeps `stars` if they beat the best rating of `level`, returning whether they did.
+    fn record(&mut self, level: usize, stars: u32) -> bool {
+        if self.best(level).map_or(false, |best| best >= stars) {
+            return false;
+        }
+
+        self.progress.stars.insert(level, stars);
+        persist::save_ron(PROGRESS, &self.progress);
+        true
+    }
+}
+
+fn read_definitions(world: &mut World) {
+    let levels = definitions::read(world, &world.resource::<Puzzles>().source);
+    world.resource_mut::<Puzzles>().levels = levels;
+}
+
+/// The level of the current or next puzzle run, and how it ended.
+#[derive(Resource, Default)]
+pub struct PuzzleRun {
+    pub level: usize,
+    outcome: Option<Outcome>,
+    /// Until the flower sinks, for [`Goal::ReachFlower`].
+    flower: Option<Timer>,
+}
+
+/// How a puzzle ended, other than by drowning.
+#[derive(Debug, Clone, Copy)]
+enum Outcome {
+    Solved {
+        stars: u32,
+        new_best: bool,
+    },
+    FlowerSank,
+    /// The last jump allowed by [`Level::max_jumps`] landed without solving it.
+    OutOfJumps,
+}
+
+impl PuzzleRun {
+    pub fn is_solved(&self) -> bool {
+        matches!(self.outcome, Some(Outcome::Solved { .. }))
+    }
+
+    /// The level that scores of a `mode` run are kept under, for the modes that have levels.
+    pub fn score_level(&self, mode: GameMode) -> Option<u32> {
+        (mode == GameMode::Puzzle).then_some(self.level as u32)
+    }
+}
+
+/// Whether the level list is shown on the title screen.
+#[derive(Resource, Default)]
+pub struct LevelSelect(pub bool);
+
+/// Marks the leaf to reach in [`Goal::ReachFlower`].
+#[derive(Component)]
+struct Flower;
+
+#[derive(Component)]
+struct PuzzleText;
+
+/// Every mode, so that the limit of a puzzle doesn't carry over into other runs.
+fn limit_jumps(
+    mode: Res<GameMode>,
+    puzzles: Res<Puzzles>,
+    run: Res<PuzzleRun>,
+    mut jumps_left: ResMut<JumpsLeft>,
+) {
+    jumps_left.0 = match *mode {
+        GameMode::Puzzle => puzzles.level(run.level).and_then(|level| level.max_jumps),
+        _ => None,
+    };
+}
+
+fn spawn_level(
+    mut commands: Commands,
+    puzzles: Res<Puzzles>,
+    mut run: ResMut<PuzzleRun>,
+    leaf_asset: Res<LeafAsset>,
+    enemy_assets: Res<EnemyAssets>,
+    mut rng: ResMut<GameRng>,
+    assets: Res<GameAssets>,
+) {
+    run.outcome = None;
+    let Some(level) = puzzles.level(run.level) else {
+        warn!("no puzzle level {}", run.level);
+        return;
+    };
+    run.flower = match level.goal {
+        Goal::EatAll => None,
+        Goal::ReachFlower { seconds, .. } => Some(Timer::from_seconds(seconds, TimerMode::Once)),
+    };
+
+    level.spawn_leaves(&mut commands, &leaf_asset, &mut rng);
+    level.spawn_bugs(&mut commands, &enemy_assets);
+
+    commands.spawn((
+        TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    right: Val::Px(15.0),
+                    ..default()
+                },
+                ..default()
+            },
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.0,
+                    color: Color::SEA_GREEN,
+                },
+            )
+            .with_alignment(TextAlignment::Right),
+            ..default()
+        },
+        PuzzleText,
+        run_scope(),
+    ));
+}
+
+fn check_goal(
+    mut landings: EventReader<LandingEvent>,
+    bugs: Query<(), With<BugPath>>,
+    mut flowers: Query<&mut Leaf, With<Flower>>,
+    stats: Res<RunStats>,
+    jumps_left: Res<JumpsLeft>,
+    time: Res<Time>,
+    attract: Option<Res<AttractMode>>,
+    no_persistence: Option<Res<NoPersistence>>,
+    mut puzzles: ResMut<Puzzles>,
+    mut run: ResMut<PuzzleRun>,
+    mut text: Query<&mut Text, With<PuzzleText>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(level) = puzzles.level(run.level) else { return };
+
+    let landed: Vec<IVec2> = landings.iter().map(|LandingEvent(pos)| *pos).collect();
+    let solved = match level.flower() {
+        None => bugs.is_empty(),
+        Some(flower) => landed.contains(&flower),
+    };
+    if solved {
+        let stars = level.stars(stats.jumps);
+        let saved = attract.is_none() && no_persistence.is_none();
+        let new_best = saved && puzzles.record(run.level, stars);
+        run.outcome = Some(Outcome::Solved { stars, new_best });
+        next_state.set(GameState::GameOver);
+        return;
+    }
+
+    if jumps_left.0 == Some(0) && !landed.is_empty() {
+        run.outcome = Some(Outcome::OutOfJumps);
+        next_state.set(GameState::GameOver);
+        return;
+    }
+
+    if let Some(timer) = &mut run.flower {
+        if timer.tick(time.delta()).just_finished() {
+            for mut leaf in &mut flowers {
+                leaf.decay = 1.0;
+            }
+            run.outcome = Some(Outcome::FlowerSank);
+            next_state.set(GameState::GameOver);
+        }
+    }
+
+    let mut value = level.describe();
+    value += &match level.max_jumps {
+        Some(max) => format!("\nJumps: {} / {max}", stats.jumps),
+        None => format!("\nJumps: {}", stats.jumps),
+    };
+    if let Some(timer) = &run.flower {
+        // Rounded up, like the time-attack countdown.
+        let left = timer.remaining() + Duration::from_millis(999);
+        value += &format!("\nThe flower sinks in {}", format_time(left));
+    }
+
+    let Ok(mut text) = text.get_single_mut() else { return };
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
+
+/// Lines about the level for the GameOver screen, ending with an empty one.
+pub fn summary(run: &PuzzleRun, puzzles: &Puzzles) -> String {
+    let Some(level) = puzzles.level(run.level) else { return String::new() };
+
+    let mut summary = match run.outcome {
+        Some(Outcome::Solved { stars, new_best }) => format!(
+            "{}: {stars} of 3 stars{}\n",
+            level.name,
+            if new_best { ", a new best!" } else { "" },
+        ),
+        Some(Outcome::FlowerSank) => format!("{}: the flower sank\n", level.name),
+        Some(Outcome::OutOfJumps) => format!("{}: out of jumps\n", level.name),
+        None => format!("{}: failed\n", level.name),
+    };
+    if run.is_solved() && puzzles.is_unlocked(run.level + 1) {
+        summary += "N: next puzzle\n";
+    }
+    summary + "\n"
+}
+
+fn next_level(
+    keys: Res<Input<KeyCode>>,
+    puzzles: Res<Puzzles>,
+    mut run: ResMut<PuzzleRun>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keys.just_released(KeyCode::N) && run.is_solved() && puzzles.is_unlocked(run.level + 1) {
+        run.level += 1;
+        next_state.set(GameState::InGame);
+    }
+}
+
+fn stars_label(stars: Option<u32>) -> String {
+    let stars = stars.unwrap_or(0) as usize;
+    "★".repeat(stars) + &"☆".repeat(3 - stars)
+}
+
+fn level_select(
+    mut egui_contexts: EguiContexts,
+    mut window: ResMut<LevelSelect>,
+    puzzles: Res<Puzzles>,
+    mut run: ResMut<PuzzleRun>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !window.0 {
+        return;
+    }
+
+    let mut open = true;
+    let mut picked = None;
+    egui::Window::new("Puzzles")
+        .open(&mut open)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            if puzzles.levels.is_empty() {
+                ui.label("No puzzles found.");
+                return;
+            }
+
+            egui::Grid::new("puzzles").striped(true).show(ui, |ui| {
+                for (i, level) in puzzles.levels.iter().enumerate() {
+                    let button = egui::Button::new(format!("{}. {}", i + 1, level.name));
+                    let response = ui
+                        .add_enabled(puzzles.is_unlocked(i), button)
+                        .on_disabled_hover_text("Solve the previous puzzle first");
+                    if response.clicked() {
+                        picked = Some(i);
+                    }
+                    ui.label(level.describe());
+                    ui.label(stars_label(puzzles.best(i)));
+                    ui.end_row();
+                }
+            });
+        });
+
+    if let Some(level) = picked {
+        run.level = level;
+        window.0 = false;
+        next_state.set(GameState::InGame);
+    } else if !open {
+        window.0 = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stars() {
+        let level = Level {
+            name: "Test".to_string(),
+            leaves: vec![(0, 0)],
+            bugs: Vec::new(),
+            goal: Goal::EatAll,
+            max_jumps: None,
+            stars: (4, 6),
+        };
+
+        assert_eq!(level.stars(0), 3);
+        assert_eq!(level.stars(4), 3);
+        assert_eq!(level.stars(5), 2);
+        assert_eq!(level.stars(6), 2);
+        assert_eq!(level.stars(7), 1);
+        assert_eq!(level.stars(100), 1);
+    }
+}
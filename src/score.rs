@@ -0,0 +1,188 @@
+//! Turns kills into points, with a combo multiplier, and shows them where they happened.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_tweening::{lens, Animator, EaseFunction, Tween, TweenCompleted};
+
+use crate::{enemy::EnemyKillEvent, run_scope, GameAssets, GameMode, GameState, Score};
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Combo>()
+            .add_event::<ScoreEvent>()
+            .add_system(reset_combo.in_schedule(OnEnter(GameState::InGame)))
+            .add_systems(
+                (combo_system, score_system, spawn_popups)
+                    .chain()
+                    .in_set(OnUpdate(GameState::InGame)),
+            )
+            .add_system(despawn_popups);
+    }
+}
+
+const BUG_POINTS: u32 = 1;
+/// Kills closer together than this keep the combo going.
+const COMBO_WINDOW: Duration = Duration::from_secs(2);
+const MAX_MULTIPLIER: u32 = 8;
+const POPUP_RISE: f32 = 80.0;
+const POPUP_TIME: Duration = Duration::from_millis(800);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreSource {
+    Bug,
+}
+
+pub struct ScoreEvent {
+    /// Points scored, with the multiplier applied.
+    pub points: u32,
+    pub multiplier: u32,
+    pub source: ScoreSource,
+    pub position: Vec2,
+}
+
+#[derive(Resource)]
+pub struct Combo {
+    pub multiplier: u32,
+    left: Duration,
+}
+
+impl Combo {
+    pub fn is_active(&self) -> bool {
+        self.multiplier > 1
+    }
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Self {
+            multiplier: 1,
+            left: Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Popup;
+
+fn reset_combo(mut combo: ResMut<Combo>) {
+    *combo = default();
+}
+
+fn combo_system(
+    mut kills: EventReader<EnemyKillEvent>,
+    mut combo: ResMut<Combo>,
+    mut scores: EventWriter<ScoreEvent>,
+    time: Res<Time>,
+    mode: Res<GameMode>,
+) {
+    // Time attack scores bugs eaten, nothing more.
+    let max_multiplier = match *mode {
+        GameMode::Endless | GameMode::Daily | GameMode::Puzzle | GameMode::Tutorial => {
+            MAX_MULTIPLIER
+        }
+        GameMode::TimeAttack => 1,
+    };
+
+    if !combo.left.is_zero() {
+        combo.left = combo.left.saturating_sub(time.delta());
+        if combo.left.is_zero() {
+            combo.multiplier = 1;
+        }
+    }
+
+    for kill in kills.iter() {
+        if !combo.left.is_zero() {
+            combo.multiplier += 1;
+        }
+        // Catching several bugs with one shot is worth an extra step.
+        if kill.shot_kills > 1 {
+            combo.multiplier += 1;
+        }
+        combo.multiplier = combo.multiplier.min(max_multiplier);
+        combo.left = COMBO_WINDOW;
+
+        scores.send(ScoreEvent {
+            points: BUG_POINTS * combo.multiplier,
+            multiplier: combo.multiplier,
+            source: ScoreSource::Bug,
+            position: kill.position,
+        });
+    }
+}
+
+fn score_system(mut scores: EventReader<ScoreEvent>, mut score: ResMut<Score>) {
+    let points = scores.iter().map(|ev| ev.points).sum::<u32>();
+    if points > 0 {
+        score.0 += points;
+    }
+}
+
+fn spawn_popups(
+    mut commands: Commands,
+    mut scores: EventReader<ScoreEvent>,
+    assets: Res<GameAssets>,
+) {
+    for ev in scores.iter() {
+        let mut value = format!("+{}", ev.points);
+        if ev.multiplier > 1 {
+            value += &format!(" x{}", ev.multiplier);
+        }
+        let color = if ev.multiplier > 1 {
+            Color::GOLD
+        } else {
+            Color::WHITE
+        };
+        let style = TextStyle {
+            font: assets.font.clone(),
+            font_size: 36.0,
+            color,
+        };
+
+        let start = ev.position.extend(10.);
+        let rise = Tween::new(
+            EaseFunction::QuadraticOut,
+            POPUP_TIME,
+            lens::TransformPositionLens {
+                start,
+                end: start + Vec3::Y * POPUP_RISE,
+            },
+        )
+        .with_completed_event(0);
+        let fade = Tween::new(
+            EaseFunction::QuadraticIn,
+            POPUP_TIME,
+            lens::TextColorLens {
+                start: color,
+                end: color.with_a(0.),
+                section: 0,
+            },
+        );
+
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(value, style).with_alignment(TextAlignment::Center),
+                transform: Transform::from_translation(start),
+                ..default()
+            },
+            Animator::new(rise),
+            Animator::new(fade),
+            Popup,
+            run_scope(),
+        ));
+    }
+}
+
+fn despawn_popups(
+    mut commands: Commands,
+    mut completed: EventReader<TweenCompleted>,
+    popups: Query<(), With<Popup>>,
+) {
+    for ev in completed.iter() {
+        if popups.contains(ev.entity) {
+            commands.entity(ev.entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Fades the screen to black and back whenever [`GameState`] changes.
+
+use std::time::Duration;
+
+use bevy::{input::InputSystem, prelude::*};
+use leafwing_input_manager::plugin::InputManagerSystem;
+
+use crate::GameState;
+
+pub struct ScreenTransitionPlugin;
+
+impl Plugin for ScreenTransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenTransitionConfig>()
+            .init_resource::<Fade>()
+            .init_resource::<PendingState>()
+            // The run stands still while the screen goes dark, instead of going on without
+            // anyone seeing it.
+            .configure_set(
+                OnUpdate(GameState::InGame)
+                    .run_if(|pending: Res<PendingState>| pending.0.is_none()),
+            )
+            .add_startup_system(spawn_overlay)
+            // Runs before `CoreSet::StateTransitions`, so that it sees `NextState` before it's applied.
+            .add_system(fade_system.in_base_set(CoreSet::PreUpdate))
+            .add_system(
+                block_input
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(InputSystem)
+                    .before(InputManagerSystem::Update)
+                    .run_if(|fade: Res<Fade>| !matches!(fade.phase, Phase::Idle)),
+            );
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct ScreenTransitionConfig {
+    /// Length of the whole wipe. The state switches halfway through. Zero disables the wipe.
+    pub duration: Duration,
+}
+
+impl Default for ScreenTransitionConfig {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_millis(500),
+        }
+    }
+}
+
+/// The state the screen is fading out to. [`NextState`] only gets it once the screen is black.
+#[derive(Resource, Default)]
+pub struct PendingState(pub Option<GameState>);
+
+enum Phase {
+    Idle,
+    Out,
+    In,
+}
+
+#[derive(Resource)]
+struct Fade {
+    phase: Phase,
+    timer: Timer,
+}
+
+impl Default for Fade {
+    fn default() -> Self {
+        Self {
+            phase: Phase::Idle,
+            timer: Timer::default(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct Overlay;
+
+fn spawn_overlay(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+        Overlay,
+    ));
+}
+
+fn fade_system(
+    mut fade: ResMut<Fade>,
+    mut pending: ResMut<PendingState>,
+    mut next_state: ResMut<NextState<GameState>>,
+    config: Res<ScreenTransitionConfig>,
+    time: Res<Time>,
+    mut overlay: Query<&mut BackgroundColor, With<Overlay>>,
+) {
+    let half = config.duration / 2;
+    let Fade { phase, timer } = &mut *fade;
+
+    let alpha = match phase {
+        Phase::Idle => {
+            if half.is_zero() {
+                return;
+            }
+            // Hold the transition back until the screen is black.
+            let Some(target) = next_state.0.take() else { return };
+            pending.0 = Some(target);
+            *phase = Phase::Out;
+            *timer = Timer::new(half, TimerMode::Once);
+            0.
+        }
+        Phase::Out => {
+            // Systems outside the run may still request another state.
+            if let Some(requested) = next_state.0.take() {
+                pending.0 = Some(requested);
+            }
+
+            if timer.tick(time.delta()).finished() {
+                next_state.0 = pending.0.take();
+                *phase = Phase::In;
+                *timer = Timer::new(half, TimerMode::Once);
+                1.
+            } else {
+                timer.percent()
+            }
+        }
+        Phase::In => {
+            if timer.tick(time.delta()).finished() {
+                *phase = Phase::Idle;
+            }
+            timer.percent_left()
+        }
+    };
+
+    overlay.single_mut().0 = Color::rgba(0., 0., 0., alpha);
+}
+
+fn block_input(mut keys: ResMut<Input<KeyCode>>, mut buttons: ResMut<Input<MouseButton>>) {
+    keys.reset_all();
+    buttons.reset_all();
+}
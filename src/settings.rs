@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+
+use bevy::{prelude::*, window::WindowMode};
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::leaderboard::LeaderboardConfig;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load())
+            .init_resource::<SettingsWindow>()
+            .add_system(apply_window_settings)
+            .add_system(settings_window);
+    }
+}
+
+const RESOLUTIONS: [(f32, f32); 3] = [(1280., 720.), (1600., 900.), (1920., 1080.)];
+
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub fullscreen: bool,
+    /// Logical window size while windowed.
+    pub resolution: (f32, f32),
+    pub screen_shake: bool,
+    /// Zoom the camera in and out while jumping.
+    pub camera_zoom: bool,
+    /// Asset packs from `assets/packs/`, each overriding the ones before. Read at startup.
+    pub asset_packs: Vec<String>,
+    /// Where the scores of finished runs are also submitted to.
+    pub leaderboard: LeaderboardConfig,
+    /// Id of the frog skin.
+    pub skin: String,
+    /// Only the first daily challenge of the day counts for the scores.
+    pub daily_first_attempt_only: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            music_volume: 0.2,
+            sfx_volume: 1.0,
+            fullscreen: false,
+            resolution: RESOLUTIONS[0],
+            screen_shake: true,
+            camera_zoom: true,
+            asset_packs: Vec::new(),
+            leaderboard: LeaderboardConfig::None,
+            skin: "classic".to_string(),
+            daily_first_attempt_only: false,
+        }
+    }
+}
+
+impl Settings {
+    fn path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("leafrog").join("settings.ron"))
+    }
+
+    /// Reads the settings file, falling back to the defaults if it's missing or broken.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else { return default() };
+        let Ok(text) = std::fs::read_to_string(&path) else { return default() };
+
+        ron::from_str(&text).unwrap_or_else(|e| {
+            warn!("ignoring {}: {e}", path.display());
+            default()
+        })
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::path() else {
+            warn!("no config directory to save settings to");
+            return;
+        };
+
+        let result = ron::ser::to_string_pretty(self, default())
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                std::fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+                std::fs::write(&path, text).map_err(|e| e.to_string())
+            });
+
+        match result {
+            Ok(()) => info!("saved settings to {}", path.display()),
+            Err(e) => error!("failed to save settings to {}: {e}", path.display()),
+        }
+    }
+}
+
+/// Whether the settings window is shown.
+#[derive(Resource, Default)]
+pub struct SettingsWindow(pub bool);
+
+fn apply_window_settings(settings: Res<Settings>, mut windows: Query<&mut Window>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let mut window = windows.single_mut();
+    window.resizable = false;
+    if settings.fullscreen {
+        window.mode = WindowMode::BorderlessFullscreen;
+    } else {
+        window.mode = WindowMode::Windowed;
+        window
+            .resolution
+            .set(settings.resolution.0, settings.resolution.1);
+    }
+}
+
+fn settings_window(
+    mut egui_contexts: EguiContexts,
+    mut open: ResMut<SettingsWindow>,
+    mut settings: ResMut<Settings>,
+) {
+    if !open.0 {
+        return;
+    }
+
+    // Edit a copy, so that change detection only fires on actual changes.
+    let mut edited = settings.clone();
+    let mut close = false;
+
+    egui::Window::new("Settings")
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.add(egui::Slider::new(&mut edited.music_volume, 0.0..=1.0).text("Music volume"));
+            ui.add(egui::Slider::new(&mut edited.sfx_volume, 0.0..=1.0).text("SFX volume"));
+
+            ui.checkbox(&mut edited.fullscreen, "Fullscreen");
+            ui.add_enabled_ui(!edited.fullscreen, |ui| {
+                egui::ComboBox::from_label("Resolution")
+                    .selected_text(format!("{}x{}", edited.resolution.0, edited.resolution.1))
+                    .show_ui(ui, |ui| {
+                        for res in RESOLUTIONS {
+                            ui.selectable_value(
+                                &mut edited.resolution,
+                                res,
+                                format!("{}x{}", res.0, res.1),
+                            );
+                        }
+                    });
+            });
+
+            ui.checkbox(&mut edited.screen_shake, "Screen shake");
+            ui.checkbox(&mut edited.camera_zoom, "Camera zoom on jump");
+            ui.checkbox(
+                &mut edited.daily_first_attempt_only,
+                "Only the first daily challenge counts",
+            );
+
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    edited.save();
+                }
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    if edited != *settings {
+        *settings = edited;
+    }
+    if close {
+        open.0 = false;
+    }
+}
@@ -0,0 +1,231 @@
+//! Frog skins from `assets/skins.ron`, or an asset pack's, unlocked through achievements and
+//! scores.
+//!
+//! The selected skin's textures are written into [`PlayerAssets`], which everything that
+//! draws the frog uses.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::Deserialize;
+
+use crate::{
+    achievements::Achievements,
+    definitions::{self, Definitions},
+    manifest,
+    player::PlayerAssets,
+    settings::Settings,
+    GameState,
+};
+
+pub struct SkinsPlugin;
+
+impl Plugin for SkinsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Skins>()
+            .add_system(read_definitions.in_schedule(OnExit(GameState::AssetLoading)))
+            // Not mid-run, where it would cut the jump animation short.
+            .add_system(apply_skin.run_if(not(in_state(GameState::InGame))))
+            .add_system(skin_picker.in_set(OnUpdate(GameState::Title)));
+    }
+}
+
+const DEFINITIONS: &str = "skins";
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub enum Unlock {
+    #[default]
+    Always,
+    /// By id.
+    Achievement(String),
+    /// Points in a single run.
+    Score(u32),
+}
+
+impl Unlock {
+    fn is_met(&self, achievements: &Achievements) -> bool {
+        match self {
+            Unlock::Always => true,
+            Unlock::Achievement(id) => achievements.is_unlocked(id),
+            Unlock::Score(points) => achievements.best_score() >= *points,
+        }
+    }
+
+    fn describe(&self, achievements: &Achievements) -> String {
+        match self {
+            Unlock::Always => String::new(),
+            Unlock::Achievement(id) => {
+                format!("Unlock \"{}\"", achievements.name(id).unwrap_or(id))
+            }
+            Unlock::Score(points) => format!("Score {points} points in one run"),
+        }
+    }
+}
+
+/// An entry of the skin file.
+#[derive(Deserialize)]
+#[serde(default)]
+struct SkinDefinition {
+    id: String,
+    name: String,
+    frames: [String; 3],
+    tongue: (String, String),
+    tint: Option<(f32, f32, f32)>,
+    unlock: Unlock,
+}
+
+impl Default for SkinDefinition {
+    /// The classic frog.
+    fn default() -> Self {
+        Self {
+            id: "classic".to_string(),
+            name: "Classic".to_string(),
+            frames: ["frog.0", "frog.1", "frog.2"].map(str::to_string),
+            tongue: ("tongue.base".to_string(), "tongue.tip".to_string()),
+            tint: None,
+            unlock: Unlock::Always,
+        }
+    }
+}
+
+pub struct Skin {
+    pub id: String,
+    pub name: String,
+    pub unlock: Unlock,
+    frames: [Handle<Image>; 3],
+    tongue_base: Handle<Image>,
+    tongue_tip: Handle<Image>,
+    tint: Color,
+}
+
+impl Skin {
+    fn load(world: &mut World, def: SkinDefinition) -> Self {
+        Self {
+            frames: def.frames.map(|id| manifest::load(world, &id)),
+            tongue_base: manifest::load(world, &def.tongue.0),
+            tongue_tip: manifest::load(world, &def.tongue.1),
+            tint: def
+                .tint
+                .map_or(Color::WHITE, |(r, g, b)| Color::rgb(r, g, b)),
+            id: def.id,
+            name: def.name,
+            unlock: def.unlock,
+        }
+    }
+}
+
+/// Every skin, in the order of the file. The first one is the fallback for locked choices.
+#[derive(Resource)]
+pub struct Skins {
+    source: Handle<Definitions>,
+    /// Only the classic frog until the assets are loaded.
+    skins: Vec<Skin>,
+}
+
+impl FromWorld for Skins {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            source: manifest::load(world, DEFINITIONS),
+            skins: vec![Skin::load(world, default())],
+        }
+    }
+}
+
+impl Skins {
+    /// The skin with `id`, or the first one if it's locked or unknown.
+    pub fn selected(&self, id: &str, achievements: &Achievements) -> &Skin {
+        self.skins
+            .iter()
+            .find(|skin| skin.id == id && skin.unlock.is_met(achievements))
+            .unwrap_or(&self.skins[0])
+    }
+}
+
+fn read_definitions(world: &mut World) {
+    let source = world.resource::<Skins>().source.clone();
+    let mut definitions: Vec<SkinDefinition> = definitions::read(world, &source);
+    if definitions.is_empty() {
+        definitions.push(default());
+    }
+
+    let skins = definitions
+        .into_iter()
+        .map(|def| Skin::load(world, def))
+        .collect();
+    world.resource_mut::<Skins>().skins = skins;
+}
+
+/// Which part of the selected skin this sprite shows.
+#[derive(Component, Clone, Copy)]
+pub enum Skinned {
+    /// Its first frame, while standing still.
+    Frog,
+    TongueBase,
+    TongueTip,
+}
+
+/// Copies the selected skin into [`PlayerAssets`] and onto the sprites already showing it.
+fn apply_skin(
+    settings: Res<Settings>,
+    achievements: Res<Achievements>,
+    skins: Res<Skins>,
+    mut assets: ResMut<PlayerAssets>,
+    mut sprites: Query<(&Skinned, &mut Handle<Image>, &mut Sprite)>,
+    mut applied: Local<Option<String>>,
+) {
+    let skin = skins.selected(&settings.skin, &achievements);
+    if applied.as_ref() == Some(&skin.id) && !skins.is_changed() {
+        return;
+    }
+    *applied = Some(skin.id.clone());
+
+    assets.player = skin.frames.clone();
+    assets.tongue_base = skin.tongue_base.clone();
+    assets.tongue_tip = skin.tongue_tip.clone();
+    assets.tint = skin.tint;
+
+    for (part, mut texture, mut sprite) in &mut sprites {
+        match part {
+            Skinned::Frog => {
+                *texture = skin.frames[0].clone();
+                sprite.color = skin.tint;
+            }
+            Skinned::TongueBase => *texture = skin.tongue_base.clone(),
+            Skinned::TongueTip => *texture = skin.tongue_tip.clone(),
+        }
+    }
+}
+
+fn skin_picker(
+    mut egui_contexts: EguiContexts,
+    skins: Res<Skins>,
+    achievements: Res<Achievements>,
+    mut settings: ResMut<Settings>,
+) {
+    let selected = skins.selected(&settings.skin, &achievements);
+    let mut chosen = None;
+
+    egui::Area::new("skin_picker")
+        .anchor(egui::Align2::LEFT_BOTTOM, [10., -10.])
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::ComboBox::from_label("Skin")
+                .selected_text(&selected.name)
+                .show_ui(ui, |ui| {
+                    for skin in &skins.skins {
+                        let label = egui::SelectableLabel::new(skin.id == selected.id, &skin.name);
+                        let response = ui
+                            .add_enabled(skin.unlock.is_met(&achievements), label)
+                            .on_disabled_hover_text(skin.unlock.describe(&achievements));
+                        if response.clicked() {
+                            chosen = Some(skin.id.clone());
+                        }
+                    }
+                });
+        });
+
+    if let Some(id) = chosen {
+        if id != settings.skin {
+            settings.skin = id;
+            settings.save();
+        }
+    }
+}
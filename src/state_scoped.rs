@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+/// Despawns the entity, with its children, when state `S` exits the state it's bound to.
+#[derive(Component, Debug, Clone)]
+pub struct DespawnOnExit<S: States> {
+    state: S,
+    carry_into: Vec<S>,
+}
+
+impl<S: States> DespawnOnExit<S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            carry_into: Vec::new(),
+        }
+    }
+
+    /// Keeps the entity through a transition into `state`, binding it to that one instead.
+    pub fn carry_into(mut self, state: S) -> Self {
+        self.carry_into.push(state);
+        self
+    }
+}
+
+/// Runs in `OnExit(exited)`, where `State` already holds the entered state.
+fn despawn_on_exit<S: States>(
+    exited: S,
+) -> impl FnMut(Commands, Query<(Entity, &mut DespawnOnExit<S>)>, Res<State<S>>) {
+    move |mut commands, mut q, state| {
+        let entered = &state.0;
+        for (entity, mut scope) in &mut q {
+            if scope.state != exited {
+                continue;
+            }
+
+            if *entered != exited && scope.carry_into.contains(entered) {
+                scope.state = entered.clone();
+            } else {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+pub struct StateScopedPlugin<S>(PhantomData<S>);
+
+impl<S> Default for StateScopedPlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: States> Plugin for StateScopedPlugin<S> {
+    fn build(&self, app: &mut App) {
+        for v in S::variants() {
+            app.add_system(despawn_on_exit(v.clone()).in_schedule(OnExit(v)));
+        }
+    }
+}
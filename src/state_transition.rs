@@ -1,18 +1,26 @@
 use bevy::prelude::*;
 use std::marker::PhantomData;
 
-pub struct StateTransitionEvent<S>(PhantomData<S>);
-
-impl<S> Default for StateTransitionEvent<S> {
-    fn default() -> Self {
-        Self(PhantomData)
-    }
+/// Sent once per transition of `S`, before its `OnExit` and `OnEnter` schedules run.
+///
+/// Entering the initial state at startup is not a transition. Setting `NextState` to the
+/// current state is one, with `from == to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateTransitionEvent<S> {
+    pub from: S,
+    pub to: S,
 }
 
 fn send_transition_event<S: States>(
+    state: Res<State<S>>,
+    next_state: Res<NextState<S>>,
     mut tx: EventWriter<StateTransitionEvent<S>>,
 ) {
-    tx.send_default();
+    let Some(to) = next_state.0.clone() else { return };
+    tx.send(StateTransitionEvent {
+        from: state.0.clone(),
+        to,
+    });
 }
 
 pub struct StateTransitionDetectorPlugin<S>(PhantomData<S>);
@@ -23,23 +31,13 @@ impl<S> Default for StateTransitionDetectorPlugin<S> {
     }
 }
 
-// #[derive(StageLabel, Clone, Hash, Debug, Eq, PartialEq, Ord, PartialOrd)]
-// struct StateTransitionDetectorStage;
-
 impl<S: States> Plugin for StateTransitionDetectorPlugin<S> {
     fn build(&self, app: &mut App) {
-        // let stage = SystemStage::parallel().with_system(state_transition_detector::<S>);
-
-        // // detector must run before NextState is removed in StateTransitionStage.
-        // app.add_event::<StateTransitionEvent<S>>().add_stage_before(
-        //     iyes_loopless::state::StateTransitionStageLabel::from_type::<S>(),
-        //     StateTransitionDetectorStage,
-        //     stage,
-        // );
-        app.add_event::<StateTransitionEvent<S>>();
-
-        for v in S::variants() {
-            app.add_system(send_transition_event::<S>.in_schedule(OnEnter(v)));
-        }
+        // The detector must run before `NextState` is taken by `apply_state_transition`.
+        app.add_event::<StateTransitionEvent<S>>().add_system(
+            send_transition_event::<S>
+                .in_base_set(CoreSet::StateTransitions)
+                .before(apply_state_transition::<S>),
+        );
     }
 }
@@ -0,0 +1,121 @@
+//! What happened during a run, for the GameOver screen and the high-score table.
+
+use std::collections::{BTreeMap, HashSet};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    enemy::{Bug, EnemyKillEvent, Species},
+    player::{DeathCause, DrownEvent, LandingEvent, TongueShotEvent},
+    score::ScoreEvent,
+    GameState,
+};
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .init_resource::<VisitedCells>()
+            .add_system(reset_stats.in_schedule(OnEnter(GameState::InGame)))
+            // Not limited to the run, so that events from its last frame still count.
+            .add_system(count_jumps)
+            .add_system(count_shots)
+            .add_system(count_combos)
+            .add_system(
+                record_death
+                    .in_base_set(CoreSet::StateTransitions)
+                    .before(apply_state_transition::<GameState>),
+            );
+    }
+}
+
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunStats {
+    pub jumps: u32,
+    /// Different cells the frog stood on, the first one included.
+    pub cells_visited: u32,
+    pub bugs_eaten: BTreeMap<Species, u32>,
+    pub shots: u32,
+    /// Shots that caught at least one bug.
+    pub hits: u32,
+    /// Highest multiplier reached.
+    pub longest_combo: u32,
+    /// Times the frog drowned without ending the run, in time attack.
+    pub drownings: u32,
+    /// What ended the run, if the frog drowned.
+    pub death: Option<DeathCause>,
+}
+
+impl RunStats {
+    /// Share of shots that caught something, from 0 to 1.
+    pub fn accuracy(&self) -> f32 {
+        if self.shots == 0 {
+            0.
+        } else {
+            self.hits as f32 / self.shots as f32
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct VisitedCells(HashSet<IVec2>);
+
+fn reset_stats(mut stats: ResMut<RunStats>, mut visited: ResMut<VisitedCells>) {
+    *stats = RunStats {
+        cells_visited: 1,
+        ..default()
+    };
+    // The frog starts on the centre leaf.
+    visited.0 = HashSet::from([IVec2::ZERO]);
+}
+
+pub fn count_jumps(
+    mut landings: EventReader<LandingEvent>,
+    mut stats: ResMut<RunStats>,
+    mut visited: ResMut<VisitedCells>,
+) {
+    for LandingEvent(pos) in landings.iter() {
+        stats.jumps += 1;
+        if visited.0.insert(*pos) {
+            stats.cells_visited += 1;
+        }
+    }
+}
+
+fn count_shots(
+    mut shots: EventReader<TongueShotEvent>,
+    mut kills: EventReader<EnemyKillEvent>,
+    bugs: Query<&Bug>,
+    mut stats: ResMut<RunStats>,
+) {
+    stats.shots += shots.iter().count() as u32;
+
+    for kill in kills.iter() {
+        // Only the first bug of a shot makes it a hit.
+        if kill.shot_kills == 1 {
+            stats.hits += 1;
+        }
+        let species = bugs.get(kill.bug).map_or(default(), |bug| bug.species);
+        *stats.bugs_eaten.entry(species).or_default() += 1;
+    }
+}
+
+fn count_combos(mut scores: EventReader<ScoreEvent>, mut stats: ResMut<RunStats>) {
+    for ev in scores.iter() {
+        stats.longest_combo = stats.longest_combo.max(ev.multiplier);
+    }
+}
+
+/// Runs before the transition, so that the GameOver screen already knows.
+fn record_death(mut drowns: EventReader<DrownEvent>, mut stats: ResMut<RunStats>) {
+    for &DrownEvent { cause, fatal } in drowns.iter() {
+        if fatal {
+            stats.death = Some(cause);
+        } else {
+            stats.drownings += 1;
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! How dangerous the run currently is, which brings in the upper music stems.
+
+use bevy::prelude::*;
+
+use crate::{
+    leaf::{Leaf, SAFE_DECAY},
+    player::PlayerPos,
+    score::Combo,
+    GameState,
+};
+
+pub struct TensionPlugin;
+
+impl Plugin for TensionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tension>()
+            .add_system(reset_tension.in_schedule(OnEnter(GameState::InGame)))
+            .add_system(update_tension.in_set(OnUpdate(GameState::InGame)));
+    }
+}
+
+/// Decay of the frog's leaf above which it gets tense.
+const DECAY_THRESHOLD: f32 = 0.5;
+/// How fast tension follows the situation, per second.
+const TENSION_RATE: f32 = 2.0;
+
+/// In `0..=1`, smoothed.
+#[derive(Resource, Default, Debug)]
+pub struct Tension(pub f32);
+
+fn reset_tension(mut tension: ResMut<Tension>) {
+    *tension = default();
+}
+
+fn update_tension(
+    mut tension: ResMut<Tension>,
+    combo: Res<Combo>,
+    player_pos: Res<PlayerPos>,
+    leaves: Query<&Leaf>,
+    time: Res<Time>,
+) {
+    let decay_at = |pos: IVec2| {
+        leaves
+            .iter()
+            .find(|leaf| leaf.pos == pos)
+            .map(|leaf| leaf.decay)
+    };
+
+    let sinking = decay_at(player_pos.0).map_or(1., |decay| {
+        ((decay - DECAY_THRESHOLD) / (1. - DECAY_THRESHOLD)).clamp(0., 1.)
+    });
+
+    let safe_neighbours = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+        .into_iter()
+        .filter(|&dir| decay_at(player_pos.0 + dir).map_or(false, |decay| decay < SAFE_DECAY))
+        .count();
+    let cornered = match safe_neighbours {
+        0 => 1.,
+        1 => 0.5,
+        _ => 0.,
+    };
+
+    let combo = ((combo.multiplier - 1) as f32 / 3.).min(1.);
+
+    let target = sinking.max(cornered).max(combo);
+    let blend = 1. - (-TENSION_RATE * time.delta_seconds()).exp();
+    tension.0 += (target - tension.0) * blend;
+}
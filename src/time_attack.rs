@@ -0,0 +1,52 @@
+//! [`GameMode::TimeAttack`]: the run ends when the time is up, and drowning costs time.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{player::DrownEvent, GameMode, GameState};
+
+pub struct TimeAttackPlugin;
+
+impl Plugin for TimeAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeLeft>()
+            .add_system(reset_time.in_schedule(OnEnter(GameState::InGame)))
+            .add_system(
+                count_down
+                    .in_set(OnUpdate(GameState::InGame))
+                    .run_if(resource_equals(GameMode::TimeAttack)),
+            );
+    }
+}
+
+pub const TIME_LIMIT: Duration = Duration::from_secs(90);
+pub const DROWN_PENALTY: Duration = Duration::from_secs(10);
+
+/// Time left in a time-attack run.
+#[derive(Resource)]
+pub struct TimeLeft(pub Duration);
+
+impl Default for TimeLeft {
+    fn default() -> Self {
+        Self(TIME_LIMIT)
+    }
+}
+
+fn reset_time(mut left: ResMut<TimeLeft>) {
+    *left = default();
+}
+
+fn count_down(
+    mut drowns: EventReader<DrownEvent>,
+    mut left: ResMut<TimeLeft>,
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let penalty = DROWN_PENALTY * drowns.iter().count() as u32;
+    left.0 = left.0.saturating_sub(time.delta() + penalty);
+
+    if left.0.is_zero() {
+        next_state.set(GameState::GameOver);
+    }
+}
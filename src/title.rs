@@ -1,7 +1,21 @@
+use std::time::Duration;
+
 use bevy::{prelude::*, sprite::Anchor};
-use bevy_egui::EguiContexts;
+use bevy_egui::{egui, EguiContexts};
 
-use crate::{player::PlayerAssets, GameAssets, MainCamera};
+use crate::{
+    achievements::AchievementsWindow,
+    autopilot::Autopilot,
+    daily::{today, DailyRecords},
+    highscore::HighScoreWindow,
+    player::PlayerAssets,
+    puzzle::LevelSelect,
+    run_scope,
+    settings::SettingsWindow,
+    skins::Skinned,
+    state_scoped::DespawnOnExit,
+    GameAssets, GameMode, MainCamera,
+};
 
 use super::GameState;
 
@@ -10,13 +24,33 @@ pub struct TitlePlugin;
 impl Plugin for TitlePlugin {
     fn build(&self, app: &mut App) {
         app.add_system(setup_title.in_schedule(OnEnter(GameState::Title)))
-            .add_system(despawn_title.in_schedule(OnExit(GameState::Title)))
-            .add_systems((frog_scale, control).in_set(OnUpdate(GameState::Title)));
+            .add_systems((frog_scale, control, title_menu).in_set(OnUpdate(GameState::Title)))
+            .add_system(
+                setup_attract_overlay
+                    .in_schedule(OnEnter(GameState::InGame))
+                    .run_if(resource_exists::<AttractMode>()),
+            )
+            .add_system(
+                attract_control
+                    .in_set(OnUpdate(GameState::InGame))
+                    .run_if(resource_exists::<AttractMode>()),
+            );
     }
 }
 
-#[derive(Component)]
-struct Title;
+/// How long the title screen waits for input before starting the demo.
+const ATTRACT_DELAY: Duration = Duration::from_secs(15);
+
+/// Exists while the attract-mode demo is running.
+#[derive(Resource)]
+pub struct AttractMode {
+    /// The mode picked on the title screen, given back after the demo, which always plays
+    /// [`GameMode::Endless`].
+    selected: GameMode,
+}
+
+#[derive(Resource)]
+struct IdleTimer(Timer);
 
 #[derive(Component)]
 struct Frog;
@@ -26,49 +60,79 @@ fn setup_title(
     assets: Res<PlayerAssets>,
     mut transform: Query<&mut Transform, With<MainCamera>>,
     game_assets: Res<GameAssets>,
+    attract: Option<Res<AttractMode>>,
+    mut mode: ResMut<GameMode>,
 ) {
-    commands
-        .spawn(TextBundle {
-            style: Style {
-                align_self: AlignSelf::FlexEnd,
-                position_type: PositionType::Absolute,
-                position: UiRect {
-                    bottom: Val::Px(5.0),
-                    right: Val::Px(15.0),
-                    ..default()
-                },
-                ..default()
-            },
-            // Use the `Text::with_section` constructor
-            text: Text::from_section(
-                // Accepts a `String` or any type that converts into a `String`, such as `&str`
-                "LEAFROG",
-                TextStyle {
-                    font: game_assets.font.clone(),
-                    font_size: 150.0,
-                    color: Color::SEA_GREEN,
-                },
-            )
-            .with_alignment(TextAlignment::Center),
-            ..default()
-        })
-        .insert(Title);
+    if let Some(attract) = attract {
+        *mode = attract.selected;
+    }
+    commands.remove_resource::<AttractMode>();
+    commands.remove_resource::<Autopilot>();
+    commands.insert_resource(IdleTimer(Timer::new(ATTRACT_DELAY, TimerMode::Once)));
+
+    spawn_title_text(&mut commands, &game_assets)
+        .insert(DespawnOnExit::new(GameState::Title));
 
     let frog = SpriteBundle {
         texture: assets.player[0].clone(),
         transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1.0))
             .with_scale(Vec3::new(3.0, 3.0, 1.0)),
         sprite: Sprite {
+            color: assets.tint,
             anchor: Anchor::Custom(Vec2::new(0.0, (64. - 50.) / 64.)),
             ..default()
         },
         ..default()
     };
-    commands.spawn((frog, Frog, Title));
+    commands.spawn((
+        frog,
+        Frog,
+        Skinned::Frog,
+        DespawnOnExit::new(GameState::Title),
+    ));
 
     transform.single_mut().translation = Vec3::new(0., 0., 999.0);
 }
 
+fn spawn_title_text<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    game_assets: &GameAssets,
+) -> bevy::ecs::system::EntityCommands<'w, 's, 'a> {
+    commands.spawn(TextBundle {
+        style: Style {
+            align_self: AlignSelf::FlexEnd,
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                bottom: Val::Px(5.0),
+                right: Val::Px(15.0),
+                ..default()
+            },
+            ..default()
+        },
+        // Use the `Text::with_section` constructor
+        text: Text::from_section(
+            // Accepts a `String` or any type that converts into a `String`, such as `&str`
+            "LEAFROG",
+            TextStyle {
+                font: game_assets.font.clone(),
+                font_size: 150.0,
+                color: Color::SEA_GREEN,
+            },
+        )
+        .with_alignment(TextAlignment::Center),
+        ..default()
+    })
+}
+
+fn setup_attract_overlay(mut commands: Commands, game_assets: Res<GameAssets>) {
+    spawn_title_text(&mut commands, &game_assets)
+        .insert(Style {
+            margin: UiRect::all(Val::Auto),
+            ..default()
+        })
+        .insert(run_scope());
+}
+
 fn frog_scale(mut frog: Query<&mut Transform, With<Frog>>, time: Res<Time>) {
     let mut tr = frog.single_mut();
     tr.scale = Vec2::splat(3.0 + 0.5 * f32::sin(time.elapsed_seconds() * std::f32::consts::PI))
@@ -76,21 +140,105 @@ fn frog_scale(mut frog: Query<&mut Transform, With<Frog>>, time: Res<Time>) {
 }
 
 fn control(
+    mut commands: Commands,
     buttons: Res<Input<MouseButton>>,
     keys: Res<Input<KeyCode>>,
+    cursor: EventReader<CursorMoved>,
+    time: Res<Time>,
+    mut idle: ResMut<IdleTimer>,
+    settings_window: Res<SettingsWindow>,
+    high_score_window: Res<HighScoreWindow>,
+    achievements_window: Res<AchievementsWindow>,
+    mut level_select: ResMut<LevelSelect>,
+    mut mode: ResMut<GameMode>,
     mut egui_contexts: EguiContexts,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
+    if keys.get_pressed().next().is_some()
+        || buttons.get_pressed().next().is_some()
+        || !cursor.is_empty()
+        || settings_window.0
+        || high_score_window.0
+        || achievements_window.0
+        || level_select.0
+    {
+        idle.0.reset();
+    } else if idle.0.tick(time.delta()).just_finished() {
+        info!("starting attract mode");
+        commands.insert_resource(AttractMode { selected: *mode });
+        commands.insert_resource(Autopilot);
+        *mode = GameMode::Endless;
+        next_state.set(GameState::InGame);
+        return;
+    }
+
     if egui_contexts.ctx_mut().is_pointer_over_area() {
         return;
     }
 
     if buttons.just_released(MouseButton::Left) || keys.just_released(KeyCode::Space) {
-        next_state.set(GameState::InGame);
+        // Puzzles start from the level list.
+        if *mode == GameMode::Puzzle {
+            level_select.0 = true;
+        } else {
+            next_state.set(GameState::InGame);
+        }
+    }
+}
+
+fn title_menu(
+    mut egui_contexts: EguiContexts,
+    mut settings_window: ResMut<SettingsWindow>,
+    mut high_score_window: ResMut<HighScoreWindow>,
+    mut achievements_window: ResMut<AchievementsWindow>,
+    mut level_select: ResMut<LevelSelect>,
+    mut mode: ResMut<GameMode>,
+    daily_records: Res<DailyRecords>,
+) {
+    let mut selected = *mode;
+
+    egui::Area::new("title_menu")
+        .anchor(egui::Align2::LEFT_TOP, [10., 10.])
+        .show(egui_contexts.ctx_mut(), |ui| {
+            if ui.button("Settings").clicked() {
+                settings_window.0 = true;
+            }
+            if ui.button("High scores").clicked() {
+                high_score_window.0 = true;
+            }
+            if ui.button("Achievements").clicked() {
+                achievements_window.0 = true;
+            }
+
+            egui::ComboBox::from_label("Mode")
+                .selected_text(selected.name())
+                .show_ui(ui, |ui| {
+                    for mode in GameMode::ALL {
+                        ui.selectable_value(&mut selected, mode, mode.name());
+                    }
+                });
+            if selected == GameMode::Daily {
+                ui.label(daily_records.summary(&today()));
+            }
+            if selected == GameMode::Puzzle && ui.button("Levels").clicked() {
+                level_select.0 = true;
+            }
+        });
+
+    // Only on actual changes, for change detection.
+    if selected != *mode {
+        *mode = selected;
     }
 }
 
-fn despawn_title(mut commands: Commands, q: Query<Entity, With<Title>>) {
-    info!("despawn_title");
-    q.for_each(|e| commands.entity(e).despawn_recursive());
+/// Any key or button ends the demo. Reacting to releases keeps them from also starting a game
+/// on the title screen.
+fn attract_control(
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keys.get_just_released().next().is_some() || buttons.get_just_released().next().is_some() {
+        next_state.set(GameState::GameOver);
+    }
 }
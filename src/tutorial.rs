@@ -0,0 +1,222 @@
+//! [`GameMode::Tutorial`]: prompts that walk a new player through jumping, leaf decay and the
+//! tongue on the fixed pond of `assets/tutorial.ron`. Each step waits for the event that shows
+//! it was done.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    definitions::{self, Definitions},
+    enemy::{EnemyAssets, EnemyKillEvent},
+    leaf::LeafAsset,
+    manifest,
+    player::{DeathCause, DrownEvent, LandingEvent},
+    puzzle::Level,
+    run_scope, GameAssets, GameMode, GameRng, GameState,
+};
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tutorial>()
+            .init_resource::<Pond>()
+            .add_system(read_pond.in_schedule(OnExit(GameState::AssetLoading)))
+            .add_system(
+                start_tutorial
+                    .in_schedule(OnEnter(GameState::InGame))
+                    .run_if(resource_equals(GameMode::Tutorial)),
+            )
+            .add_system(
+                advance
+                    .in_set(OnUpdate(GameState::InGame))
+                    .run_if(resource_equals(GameMode::Tutorial)),
+            );
+    }
+}
+
+const POND: &str = "tutorial";
+/// How long the last prompt stays before going back to the title screen.
+const OUTRO_TIME: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Step {
+    /// Until the frog lands somewhere.
+    #[default]
+    Jump,
+    /// Until it sinks with a leaf.
+    Sink,
+    /// Until it catches a bug.
+    Catch,
+    Done,
+}
+
+impl Step {
+    fn prompt(self) -> &'static str {
+        match self {
+            Step::Jump => {
+                "Jump to the next leaf with WASD or the arrow keys.\n\
+                 The squares at the top left turn green where it's safe to land."
+            }
+            Step::Sink => {
+                "Leaves sink while the frog sits on them, the bar at the top left shows how far.\n\
+                 Hold still and watch this one sink."
+            }
+            Step::Catch => {
+                "Splash! Outside the tutorial, that would have ended the run.\n\
+                 Click the bug to catch it with the tongue."
+            }
+            Step::Done => {
+                "Well done! Keep hopping before your leaf sinks, and eat bugs for points.\n\
+                 Catching them in quick succession builds up a combo."
+            }
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Step::Jump => Step::Sink,
+            Step::Sink => Step::Catch,
+            Step::Catch | Step::Done => Step::Done,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct Tutorial {
+    step: Step,
+    /// Started on the last step.
+    outro: Timer,
+}
+
+/// The leaves, and the bugs to catch in [`Step::Catch`].
+#[derive(Resource)]
+struct Pond {
+    source: Handle<Definitions>,
+    /// `None` until the assets are loaded.
+    level: Option<Level>,
+}
+
+impl FromWorld for Pond {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            source: manifest::load(world, POND),
+            level: None,
+        }
+    }
+}
+
+#[derive(Component)]
+struct PromptText;
+
+fn read_pond(world: &mut World) {
+    let levels: Vec<Level> = definitions::read(world, &world.resource::<Pond>().source);
+    world.resource_mut::<Pond>().level = levels.into_iter().next();
+}
+
+fn start_tutorial(
+    mut commands: Commands,
+    mut tutorial: ResMut<Tutorial>,
+    pond: Res<Pond>,
+    leaf_asset: Res<LeafAsset>,
+    mut rng: ResMut<GameRng>,
+    assets: Res<GameAssets>,
+) {
+    *tutorial = Tutorial {
+        step: default(),
+        outro: Timer::new(OUTRO_TIME, TimerMode::Once),
+    };
+
+    match &pond.level {
+        Some(level) => level.spawn_leaves(&mut commands, &leaf_asset, &mut rng),
+        None => warn!("no tutorial pond"),
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(70.0),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.), Val::Auto),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(run_scope())
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0., 0.2, 0.3, 0.8).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle {
+                            text: Text::from_section(
+                                tutorial.step.prompt(),
+                                TextStyle {
+                                    font: assets.font.clone(),
+                                    font_size: 26.0,
+                                    color: Color::WHITE,
+                                },
+                            )
+                            .with_alignment(TextAlignment::Center),
+                            ..default()
+                        },
+                        PromptText,
+                    ));
+                });
+        });
+}
+
+fn advance(
+    mut commands: Commands,
+    mut landings: EventReader<LandingEvent>,
+    mut drowns: EventReader<DrownEvent>,
+    mut kills: EventReader<EnemyKillEvent>,
+    mut tutorial: ResMut<Tutorial>,
+    pond: Res<Pond>,
+    enemy_assets: Res<EnemyAssets>,
+    time: Res<Time>,
+    mut text: Query<&mut Text, With<PromptText>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Read every frame, so that nothing done ahead of its step counts later.
+    let landed = landings.iter().count() > 0;
+    let sank = drowns.iter().any(|ev| ev.cause == DeathCause::SunkLeaf);
+    let caught = kills.iter().count() > 0;
+
+    let done = match tutorial.step {
+        Step::Jump => landed,
+        Step::Sink => sank,
+        Step::Catch => caught,
+        Step::Done => {
+            if tutorial.outro.tick(time.delta()).just_finished() {
+                next_state.set(GameState::Title);
+            }
+            false
+        }
+    };
+    if !done {
+        return;
+    }
+
+    tutorial.step = tutorial.step.next();
+    // Not before, so that none gets eaten ahead of its step.
+    if let (Step::Catch, Some(level)) = (tutorial.step, &pond.level) {
+        level.spawn_bugs(&mut commands, &enemy_assets);
+    }
+
+    if let Ok(mut text) = text.get_single_mut() {
+        text.sections[0].value = tutorial.step.prompt().to_string();
+    }
+}